@@ -0,0 +1,151 @@
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::{rpc_error_code, RpcClient, SyscoinClient, SyscoinError, RPC_WALLET_INVALID_LABEL_NAME};
+
+/// Output type for newly generated addresses
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum AddressType {
+    /// Native segwit v0 (`sys1q...`)
+    #[default]
+    Bech32,
+    /// Taproot (`sys1p...`)
+    Bech32m,
+    /// Pre-segwit P2PKH
+    Legacy,
+}
+
+impl AddressType {
+    /// Name of the type as expected by `getnewaddress`
+    pub fn as_rpc_str(self) -> &'static str {
+        match self {
+            AddressType::Bech32 => "bech32",
+            AddressType::Bech32m => "bech32m",
+            AddressType::Legacy => "legacy",
+        }
+    }
+}
+
+/// Why an address is in the wallet's address book
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AddressPurpose {
+    Receive,
+    Send,
+    #[serde(other)]
+    Other,
+}
+
+impl AddressPurpose {
+    fn as_rpc_str(self) -> Option<&'static str> {
+        match self {
+            AddressPurpose::Receive => Some("receive"),
+            AddressPurpose::Send => Some("send"),
+            AddressPurpose::Other => None,
+        }
+    }
+}
+
+/// An address carrying a given label
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LabeledAddress {
+    pub address: String,
+    pub purpose: AddressPurpose,
+}
+
+/// Typed result of `validateaddress`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct AddressValidation {
+    #[serde(rename = "isvalid")]
+    pub is_valid: bool,
+    #[serde(default)]
+    pub address: Option<String>,
+    #[serde(rename = "scriptPubKey", default)]
+    pub script_pub_key: Option<String>,
+    #[serde(rename = "isscript", default)]
+    pub is_script: Option<bool>,
+    #[serde(rename = "iswitness", default)]
+    pub is_witness: Option<bool>,
+    #[serde(default)]
+    pub witness_version: Option<u8>,
+    /// Reason the address is invalid, when the node gives one
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct LabelEntry {
+    purpose: AddressPurpose,
+}
+
+impl SyscoinClient {
+    /// Get a fresh address of `address_type` for a given label
+    pub async fn get_new_address(&self, address_label: &str, address_type: AddressType) -> Result<String, SyscoinError> {
+        let resp = self
+            .rpc_client
+            .call_wallet("getnewaddress", &[json!(address_label), json!(address_type.as_rpc_str())])
+            .await?;
+        resp.as_str()
+            .map(|s| s.to_owned())
+            .ok_or_else(|| "getnewaddress returned non-string".into())
+    }
+
+    /// Labels known to the wallet, optionally only those used for `purpose`
+    pub async fn list_labels(&self, purpose: Option<AddressPurpose>) -> Result<Vec<String>, SyscoinError> {
+        let params: Vec<Value> = purpose
+            .and_then(AddressPurpose::as_rpc_str)
+            .map(|p| vec![json!(p)])
+            .unwrap_or_default();
+        let resp = self.rpc_client.call_wallet("listlabels", &params).await?;
+        let mut labels: Vec<String> = serde_json::from_value(resp)?;
+        labels.sort();
+        Ok(labels)
+    }
+
+    /// All addresses carrying `address_label`, sorted by address so callers see a stable order.
+    /// An unknown label yields an empty list.
+    pub async fn list_addresses_by_label(&self, address_label: &str) -> Result<Vec<LabeledAddress>, SyscoinError> {
+        let resp = match self
+            .rpc_client
+            .call_wallet("getaddressesbylabel", &[json!(address_label)])
+            .await
+        {
+            Ok(v) => v,
+            // -11: "No addresses with label"
+            Err(e) if rpc_error_code(&e) == Some(RPC_WALLET_INVALID_LABEL_NAME) => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let entries: std::collections::BTreeMap<String, LabelEntry> = serde_json::from_value(resp)?;
+        Ok(entries
+            .into_iter()
+            .map(|(address, entry)| LabeledAddress { address, purpose: entry.purpose })
+            .collect())
+    }
+
+    /// Assign `label` to an address of the wallet (replacing its previous label)
+    pub async fn set_label(&self, address: &str, label: &str) -> Result<(), SyscoinError> {
+        self.rpc_client
+            .call_wallet("setlabel", &[json!(address), json!(label)])
+            .await?;
+        Ok(())
+    }
+
+    /// Check whether `address` is a valid Syscoin address for the node's network
+    pub async fn validate_address(&self, address: &str) -> Result<AddressValidation, SyscoinError> {
+        let resp = self.rpc_client.call("validateaddress", &[json!(address)]).await?;
+        Ok(serde_json::from_value(resp)?)
+    }
+
+    /// Like `ensure_address_by_label`, but creates missing addresses of `address_type`
+    pub async fn ensure_address_by_label_with_type(
+        &self,
+        address_label: &str,
+        address_type: AddressType,
+    ) -> Result<String, SyscoinError> {
+        match self.fetch_address_by_label(address_label).await? {
+            Some(existing) => Ok(existing),
+            None => self.get_new_address(address_label, address_type).await,
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use std::fmt;
+
+use crate::{SyscoinError, SATOSHIS_PER_SYS};
+
+const SATS_PER_SYS: u64 = 100_000_000;
+
+/// An amount of SYS held as whole satoshis to avoid floating point drift
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Amount(u64);
+
+impl Amount {
+    pub const ZERO: Amount = Amount(0);
+
+    pub const fn from_sat(sats: u64) -> Self {
+        Amount(sats)
+    }
+
+    /// Convert a SYS value as reported by the node, rounding to the nearest satoshi
+    pub fn from_sys(sys: f64) -> Result<Self, SyscoinError> {
+        if !sys.is_finite() || sys < 0.0 {
+            return Err(format!("invalid SYS amount: {}", sys).into());
+        }
+        Ok(Amount((sys * SATOSHIS_PER_SYS).round() as u64))
+    }
+
+    pub const fn to_sat(self) -> u64 {
+        self.0
+    }
+
+    pub fn to_sys(self) -> f64 {
+        self.0 as f64 / SATOSHIS_PER_SYS
+    }
+
+    pub fn checked_sub(self, other: Amount) -> Option<Amount> {
+        self.0.checked_sub(other.0).map(Amount)
+    }
+
+    /// Fixed-point SYS string, which the node accepts for amount arguments
+    /// without the rounding issues of JSON floats
+    pub(crate) fn to_rpc_string(self) -> String {
+        format!("{}.{:08}", self.0 / SATS_PER_SYS, self.0 % SATS_PER_SYS)
+    }
+}
+
+impl std::ops::Add for Amount {
+    type Output = Amount;
+
+    fn add(self, rhs: Amount) -> Amount {
+        Amount(self.0 + rhs.0)
+    }
+}
+
+impl std::iter::Sum for Amount {
+    fn sum<I: Iterator<Item = Amount>>(iter: I) -> Amount {
+        iter.fold(Amount::ZERO, |a, b| a + b)
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} SYS", self.to_rpc_string())
+    }
+}
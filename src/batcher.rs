@@ -0,0 +1,274 @@
+//! Packing many small items into one blob, with Merkle proofs per item.
+//!
+//! Batch layout (integers little-endian):
+//!
+//! ```text
+//! magic "SDAB" | version u8 | item count u32 | Merkle root [32]
+//! | count × item length u32 | items concatenated
+//! ```
+//!
+//! Leaves are `sha256(0x00 || item)` and inner nodes `sha256(0x01 || left || right)`.
+//! An odd node at the end of a level is carried up unchanged rather than paired
+//! with itself, so no two different item lists share a root.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::info;
+
+use crate::{SyscoinClient, SyscoinError, MAX_BLOB_SIZE};
+
+pub const BATCH_MAGIC: &[u8; 4] = b"SDAB";
+const BATCH_VERSION: u8 = 1;
+const FIXED_HEADER_LEN: usize = 4 + 1 + 4 + 32;
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+/// Proof that an item was published at `index` of the batch blob `blob_id`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ItemReceipt {
+    pub blob_id: String,
+    pub index: u32,
+    /// Number of items in the batch
+    pub item_count: u32,
+    pub merkle_root: [u8; 32],
+    /// Sibling hashes from the leaf up to the root
+    pub proof: Vec<[u8; 32]>,
+}
+
+/// Accumulates items and publishes them together once the next one would not fit
+pub struct BlobBatcher<'a> {
+    client: &'a SyscoinClient,
+    items: Vec<Vec<u8>>,
+    encoded_size: usize,
+    max_size: usize,
+}
+
+impl<'a> BlobBatcher<'a> {
+    pub fn new(client: &'a SyscoinClient) -> Self {
+        Self { client, items: Vec::new(), encoded_size: FIXED_HEADER_LEN, max_size: MAX_BLOB_SIZE }
+    }
+
+    /// Flush batches before they exceed `max_size` encoded bytes (capped at `MAX_BLOB_SIZE`)
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size.min(MAX_BLOB_SIZE);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Encoded size of the pending batch in bytes
+    pub fn pending_size(&self) -> usize {
+        self.encoded_size
+    }
+
+    /// Queue `item`. If it does not fit in the pending batch, that batch is published
+    /// first and its receipts returned. `item` stays queued even if publishing fails.
+    pub async fn push(&mut self, item: impl Into<Vec<u8>>) -> Result<Vec<ItemReceipt>, SyscoinError> {
+        let item = item.into();
+        let item_size = 4 + item.len();
+        if FIXED_HEADER_LEN + item_size > self.max_size {
+            return Err(format!(
+                "item of {} bytes does not fit in a batch of at most {} bytes",
+                item.len(),
+                self.max_size
+            )
+            .into());
+        }
+
+        self.encoded_size += item_size;
+        self.items.push(item);
+        if self.encoded_size > self.max_size {
+            self.publish_batch().await
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    /// Publish the pending items, if any, and return one receipt per item.
+    /// Items are only removed once published; after a failed publish the
+    /// pending items may span several batches, so call `flush` until `is_empty`.
+    pub async fn flush(&mut self) -> Result<Vec<ItemReceipt>, SyscoinError> {
+        if self.items.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.publish_batch().await
+    }
+
+    /// Publish the longest run of pending items that fits in `max_size`
+    async fn publish_batch(&mut self) -> Result<Vec<ItemReceipt>, SyscoinError> {
+        let mut size = FIXED_HEADER_LEN;
+        let count = self
+            .items
+            .iter()
+            .take_while(|item| {
+                size += 4 + item.len();
+                size <= self.max_size
+            })
+            .count();
+        let batch = &self.items[..count];
+
+        let leaves: Vec<[u8; 32]> = batch.iter().map(|item| leaf_hash(item)).collect();
+        let root = merkle_root(&leaves);
+        let blob = encode_batch(batch, &root);
+        let blob_id = self.client.create_blob(&blob).await?;
+        info!("published batch of {} items ({} bytes) as {}", count, blob.len(), blob_id);
+
+        let item_count = leaves.len() as u32;
+        let receipts = (0..leaves.len())
+            .map(|index| ItemReceipt {
+                blob_id: blob_id.clone(),
+                index: index as u32,
+                item_count,
+                merkle_root: root,
+                proof: merkle_proof(&leaves, index),
+            })
+            .collect();
+
+        self.encoded_size -= blob.len() - FIXED_HEADER_LEN;
+        self.items.drain(..count);
+        Ok(receipts)
+    }
+}
+
+impl SyscoinClient {
+    /// Fetch the item at `index` of a batch blob written by `BlobBatcher`.
+    /// The whole batch is checked against its Merkle root first.
+    pub async fn get_blob_item(&self, blob_id: &str, index: u32) -> Result<Vec<u8>, SyscoinError> {
+        let blob = self.get_blob(blob_id).await?;
+        let mut items = decode_batch(&blob)?;
+        let count = items.len();
+        if index as usize >= count {
+            return Err(format!("batch {} has {} items; index {} is out of range", blob_id, count, index).into());
+        }
+        Ok(items.swap_remove(index as usize))
+    }
+}
+
+/// Check that `item` is the one `receipt` commits to
+pub fn verify_item_inclusion(receipt: &ItemReceipt, item: &[u8]) -> bool {
+    if receipt.index >= receipt.item_count {
+        return false;
+    }
+
+    let mut hash = leaf_hash(item);
+    let mut index = receipt.index as usize;
+    let mut width = receipt.item_count as usize;
+    let mut siblings = receipt.proof.iter();
+    while width > 1 {
+        let carried = index == width - 1 && width & 1 == 1;
+        if !carried {
+            let Some(sibling) = siblings.next() else {
+                return false;
+            };
+            hash = if index & 1 == 0 { node_hash(&hash, sibling) } else { node_hash(sibling, &hash) };
+        }
+        index /= 2;
+        width = width.div_ceil(2);
+    }
+    siblings.next().is_none() && hash == receipt.merkle_root
+}
+
+/// Split a batch blob into its items, checking the embedded Merkle root
+pub fn decode_batch(blob: &[u8]) -> Result<Vec<Vec<u8>>, SyscoinError> {
+    if blob.len() < FIXED_HEADER_LEN || !blob.starts_with(BATCH_MAGIC) {
+        return Err("blob is not a batch".into());
+    }
+    if blob[4] != BATCH_VERSION {
+        return Err(format!("unsupported batch version {}", blob[4]).into());
+    }
+    let count = u32::from_le_bytes(blob[5..9].try_into().expect("4 bytes")) as usize;
+    let root: [u8; 32] = blob[9..FIXED_HEADER_LEN].try_into().expect("32 bytes");
+
+    let lengths_end = count
+        .checked_mul(4)
+        .and_then(|n| n.checked_add(FIXED_HEADER_LEN))
+        .filter(|&end| end <= blob.len())
+        .ok_or("batch is truncated")?;
+    let mut items = Vec::with_capacity(count);
+    let mut offset = lengths_end;
+    for chunk in blob[FIXED_HEADER_LEN..lengths_end].chunks_exact(4) {
+        let len = u32::from_le_bytes(chunk.try_into().expect("4 bytes")) as usize;
+        let item = blob.get(offset..offset + len).ok_or("batch is truncated")?;
+        items.push(item.to_vec());
+        offset += len;
+    }
+
+    let leaves: Vec<[u8; 32]> = items.iter().map(|item| leaf_hash(item)).collect();
+    if merkle_root(&leaves) != root {
+        return Err("batch contents do not match its Merkle root".into());
+    }
+    Ok(items)
+}
+
+fn encode_batch(items: &[Vec<u8>], root: &[u8; 32]) -> Vec<u8> {
+    let body: usize = items.iter().map(|i| 4 + i.len()).sum();
+    let mut out = Vec::with_capacity(FIXED_HEADER_LEN + body);
+    out.extend_from_slice(BATCH_MAGIC);
+    out.push(BATCH_VERSION);
+    out.extend_from_slice(&(items.len() as u32).to_le_bytes());
+    out.extend_from_slice(root);
+    for item in items {
+        out.extend_from_slice(&(item.len() as u32).to_le_bytes());
+    }
+    for item in items {
+        out.extend_from_slice(item);
+    }
+    out
+}
+
+fn leaf_hash(item: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([LEAF_PREFIX]);
+    hasher.update(item);
+    hasher.finalize().into()
+}
+
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([NODE_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+fn next_level(level: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => node_hash(left, right),
+            [single] => *single,
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+fn merkle_root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    if leaves.is_empty() {
+        return [0; 32];
+    }
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    level[0]
+}
+
+fn merkle_proof(leaves: &[[u8; 32]], mut index: usize) -> Vec<[u8; 32]> {
+    let mut proof = Vec::new();
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        let sibling = index ^ 1;
+        if sibling < level.len() {
+            proof.push(level[sibling]);
+        }
+        level = next_level(&level);
+        index /= 2;
+    }
+    proof
+}
@@ -1,9 +1,8 @@
 use std::time::Duration;
-use tokio::time::sleep;
 use tracing::{info, debug, span, Level};
 use tracing::{Instrument};
 use tracing_subscriber::fmt;
-use bitcoin_da_client::SyscoinClient;
+use bitcoin_da_client::{AddressType, BalanceEvent, BalanceMonitor, BalanceMonitorConfig, SyscoinClient};
 
 type Error = Box<dyn std::error::Error + Send + Sync>;
 
@@ -55,7 +54,7 @@ async fn main() -> Result<(), Error> {
     info!("🏷️ Funding label '{}' is bound to address: {}", funding_label, funding_address);
 
     // 📥 Fetch the current balance
-    let mut balance = client
+    let balance = client
         .get_balance()
         .instrument(span!(Level::INFO, "get_balance_start"))
         .await?;
@@ -76,7 +75,7 @@ async fn main() -> Result<(), Error> {
             None => {
                 info!("✨ No address yet—creating a fresh one…");
                 let addr = client
-                    .get_new_address("podalabel")
+                    .get_new_address("podalabel", AddressType::Bech32)
                     .instrument(span!(Level::DEBUG, "get_new_address", label = "podalabel"))
                     .await?;
                 info!("📍 New funding address: {}", addr);
@@ -87,11 +86,28 @@ async fn main() -> Result<(), Error> {
         info!("💌 Please send some SYS to: {}", address);
 
         // 🔄 Poll until funds arrive
-        while balance <= 0.0 {
-            debug!("⏳ Waiting 10 seconds before checking balance again…");
-            sleep(Duration::from_secs(10)).await;
-            balance = client.get_balance().await?;
-            info!("🔄 Checking… current balance: {} SYS", balance);
+        let mut monitor = BalanceMonitor::new(
+            &client,
+            BalanceMonitorConfig {
+                poll_interval: Duration::from_secs(10),
+                blob_size: 1024,
+                ..Default::default()
+            },
+        );
+        monitor.on_event(|event| {
+            if let BalanceEvent::BalanceChanged { current, .. } = event {
+                info!(
+                    "🔄 Checking… trusted: {} SYS, pending: {} SYS, immature: {} SYS",
+                    current.mine.trusted, current.mine.untrusted_pending, current.mine.immature
+                );
+            }
+        });
+        let snapshot = monitor
+            .wait_until(|snapshot| snapshot.balances.mine.trusted > 0.0)
+            .instrument(span!(Level::INFO, "wait_for_funds"))
+            .await?;
+        if let Some(affordable) = snapshot.affordable_blobs {
+            debug!("💰 Wallet can afford ~{} blobs of 1 KiB", affordable);
         }
         info!("🎉 Funds detected! Continuing…");
     }
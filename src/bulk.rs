@@ -0,0 +1,112 @@
+use futures_util::stream::{self, StreamExt};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::{BlobCreation, CreateBlobOptions, SyscoinClient, SyscoinError, MAX_BLOB_SIZE};
+
+const DEFAULT_CONCURRENCY: usize = 4;
+const DEFAULT_CONF_TARGET: u16 = 6;
+
+/// Options for `create_blobs`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchOptions {
+    /// Submissions in flight at once (at least one)
+    pub max_concurrency: usize,
+    /// Fund blobs one at a time behind a single lock so concurrent submissions
+    /// cannot pick the same UTXOs; duplicate checks still overlap
+    pub serialize_funding: bool,
+    pub create: CreateBlobOptions,
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        Self { max_concurrency: DEFAULT_CONCURRENCY, serialize_funding: false, create: CreateBlobOptions::default() }
+    }
+}
+
+/// Totals for a `create_blobs` call
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BatchSummary {
+    pub created: usize,
+    pub already_existed: usize,
+    pub failed: usize,
+    /// Bytes stored by newly created blobs (after compression)
+    pub total_bytes: u64,
+    /// Blob base fee for `total_bytes` at the fee rate seen before submitting, in satoshis.
+    /// `None` if the fee could not be estimated.
+    pub estimated_fee: Option<u128>,
+}
+
+/// Outcome of `create_blobs`
+#[derive(Debug)]
+pub struct BatchSubmission {
+    /// Version hash or error for each input, in input order
+    pub results: Vec<Result<String, SyscoinError>>,
+    pub summary: BatchSummary,
+}
+
+impl SyscoinClient {
+    /// Submit several blobs with bounded concurrency. Every blob is prepared and
+    /// size-checked before anything is sent, so an oversized item fails the whole
+    /// batch up front; later failures are reported per item.
+    pub async fn create_blobs(&self, blobs: &[&[u8]], options: &BatchOptions) -> Result<BatchSubmission, SyscoinError> {
+        let prepared = blobs
+            .iter()
+            .enumerate()
+            .map(|(i, data)| {
+                let (blob, _) = self.prepare_blob(data, None)?;
+                if blob.len() > MAX_BLOB_SIZE {
+                    return Err(format!("blob {} size ({}) exceeds maximum allowed ({})", i, blob.len(), MAX_BLOB_SIZE).into());
+                }
+                Ok(blob)
+            })
+            .collect::<Result<Vec<_>, SyscoinError>>()?;
+
+        let fee_per_byte = match self.get_blob_base_fee(DEFAULT_CONF_TARGET).await {
+            Ok(fee) => Some(fee),
+            Err(e) => {
+                warn!("could not estimate batch fees: {}", e);
+                None
+            }
+        };
+
+        let funding = options.serialize_funding.then(|| Mutex::new(()));
+        let outcomes: Vec<_> = stream::iter(&prepared)
+            .map(|blob| self.submit_blob_bytes_with(blob, &options.create, funding.as_ref()))
+            .buffered(options.max_concurrency.max(1))
+            .collect()
+            .await;
+
+        let mut summary = BatchSummary::default();
+        let results = outcomes
+            .into_iter()
+            .zip(&prepared)
+            .map(|(outcome, blob)| match outcome {
+                Ok(BlobCreation::Created(hash)) => {
+                    summary.created += 1;
+                    summary.total_bytes += blob.len() as u64;
+                    Ok(hash)
+                }
+                Ok(BlobCreation::AlreadyExists(hash)) => {
+                    summary.already_existed += 1;
+                    Ok(hash)
+                }
+                Err(e) => {
+                    summary.failed += 1;
+                    Err(e)
+                }
+            })
+            .collect();
+        summary.estimated_fee = fee_per_byte.map(|fee| fee * summary.total_bytes as u128);
+
+        info!(
+            "batch of {}: {} created, {} already existed, {} failed, {} bytes",
+            blobs.len(),
+            summary.created,
+            summary.already_existed,
+            summary.failed,
+            summary.total_bytes
+        );
+        Ok(BatchSubmission { results, summary })
+    }
+}
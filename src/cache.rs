@@ -0,0 +1,358 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use lru::LruCache;
+use sha2::{Digest, Sha256};
+use tracing::{debug, warn};
+
+use crate::{blob_versionhash, SyscoinClient, SyscoinError};
+
+/// Decides whether bytes fetched from PoDA cloud really are `blob_id` before they are cached
+pub type BlobVerifier = Arc<dyn Fn(&str, &[u8]) -> bool + Send + Sync>;
+
+/// Counters reported by a `BlobCache`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub insertions: u64,
+    pub evictions: u64,
+    /// Blobs currently cached
+    pub entries: u64,
+    /// Payload bytes currently cached
+    pub bytes: u64,
+}
+
+/// Storage consulted by `get_blob` before the node and PoDA cloud.
+/// Keys are normalised blob ids (lowercase hex, no `0x`). Errors are logged
+/// by the client and treated as misses.
+#[async_trait]
+pub trait BlobCache: Send + Sync {
+    async fn get(&self, blob_id: &str) -> Result<Option<Vec<u8>>, SyscoinError>;
+
+    async fn put(&self, blob_id: &str, data: &[u8]) -> Result<(), SyscoinError>;
+
+    fn stats(&self) -> CacheStats;
+}
+
+#[derive(Default)]
+struct Counters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    insertions: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl Counters {
+    fn hit(&self, found: bool) {
+        let counter = if found { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self, entries: usize, bytes: u64) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            insertions: self.insertions.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            entries: entries as u64,
+            bytes,
+        }
+    }
+}
+
+/// Byte-bounded in-memory LRU cache
+pub struct MemoryBlobCache {
+    max_bytes: u64,
+    inner: Mutex<(LruCache<String, Vec<u8>>, u64)>,
+    counters: Counters,
+}
+
+impl MemoryBlobCache {
+    /// Keep at most `max_bytes` of blob data, evicting least recently used blobs first
+    pub fn new(max_bytes: u64) -> Self {
+        Self { max_bytes, inner: Mutex::new((LruCache::unbounded(), 0)), counters: Counters::default() }
+    }
+}
+
+#[async_trait]
+impl BlobCache for MemoryBlobCache {
+    async fn get(&self, blob_id: &str) -> Result<Option<Vec<u8>>, SyscoinError> {
+        let found = self.inner.lock().unwrap().0.get(blob_id).cloned();
+        self.counters.hit(found.is_some());
+        Ok(found)
+    }
+
+    async fn put(&self, blob_id: &str, data: &[u8]) -> Result<(), SyscoinError> {
+        let size = data.len() as u64;
+        if size > self.max_bytes {
+            return Ok(());
+        }
+        let mut guard = self.inner.lock().unwrap();
+        let (lru, bytes) = &mut *guard;
+        if let Some(old) = lru.put(blob_id.to_owned(), data.to_vec()) {
+            *bytes -= old.len() as u64;
+        }
+        *bytes += size;
+        while *bytes > self.max_bytes {
+            let Some((_, evicted)) = lru.pop_lru() else { break };
+            *bytes -= evicted.len() as u64;
+            self.counters.evictions.fetch_add(1, Ordering::Relaxed);
+        }
+        self.counters.insertions.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn stats(&self) -> CacheStats {
+        let guard = self.inner.lock().unwrap();
+        self.counters.snapshot(guard.0.len(), guard.1)
+    }
+}
+
+/// Byte-bounded cache in a content-addressed directory (`<dir>/<id[..2]>/<id>`).
+/// Each file starts with the SHA-256 of its payload so corrupted entries are
+/// detected and dropped. Hits refresh a file's mtime, so entries from earlier
+/// runs are picked up on open in least recently used order.
+pub struct DiskBlobCache {
+    dir: PathBuf,
+    max_bytes: u64,
+    index: Mutex<(LruCache<String, u64>, u64)>,
+    counters: Counters,
+}
+
+impl DiskBlobCache {
+    /// Open (creating if needed) a cache in `dir` holding at most `max_bytes` of blob data
+    pub async fn open(dir: impl AsRef<Path>, max_bytes: u64) -> Result<Self, SyscoinError> {
+        let dir = dir.as_ref().to_path_buf();
+        tokio::fs::create_dir_all(&dir).await?;
+
+        let mut found: Vec<(SystemTime, String, u64)> = Vec::new();
+        let mut shards = tokio::fs::read_dir(&dir).await?;
+        while let Some(shard) = shards.next_entry().await? {
+            if !shard.file_type().await?.is_dir() {
+                continue;
+            }
+            let mut files = tokio::fs::read_dir(shard.path()).await?;
+            while let Some(file) = files.next_entry().await? {
+                let Some(name) = file.file_name().to_str().map(str::to_owned) else { continue };
+                if !is_cache_key(&name) {
+                    continue;
+                }
+                let meta = file.metadata().await?;
+                let size = meta.len().saturating_sub(32);
+                found.push((meta.modified().unwrap_or(SystemTime::UNIX_EPOCH), name, size));
+            }
+        }
+        found.sort();
+
+        let mut lru = LruCache::unbounded();
+        let mut bytes = 0;
+        for (_, name, size) in found {
+            bytes += size;
+            lru.put(name, size);
+        }
+        let cache = Self { dir, max_bytes, index: Mutex::new((lru, bytes)), counters: Counters::default() };
+        cache.evict_over_limit().await;
+        Ok(cache)
+    }
+
+    fn path_for(&self, blob_id: &str) -> PathBuf {
+        self.dir.join(&blob_id[..2]).join(blob_id)
+    }
+
+    async fn evict_over_limit(&self) {
+        loop {
+            let victim = {
+                let mut guard = self.index.lock().unwrap();
+                let (lru, bytes) = &mut *guard;
+                if *bytes <= self.max_bytes {
+                    return;
+                }
+                let Some((id, size)) = lru.pop_lru() else { return };
+                *bytes -= size;
+                id
+            };
+            self.counters.evictions.fetch_add(1, Ordering::Relaxed);
+            if let Err(e) = tokio::fs::remove_file(self.path_for(&victim)).await {
+                debug!("could not remove evicted blob {}: {}", victim, e);
+            }
+        }
+    }
+
+    fn forget(&self, blob_id: &str) {
+        let mut guard = self.index.lock().unwrap();
+        if let Some(size) = guard.0.pop(blob_id) {
+            guard.1 -= size;
+        }
+    }
+}
+
+#[async_trait]
+impl BlobCache for DiskBlobCache {
+    async fn get(&self, blob_id: &str) -> Result<Option<Vec<u8>>, SyscoinError> {
+        if !is_cache_key(blob_id) {
+            self.counters.hit(false);
+            return Ok(None);
+        }
+        let path = self.path_for(blob_id);
+        let file = match tokio::fs::read(&path).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                self.forget(blob_id);
+                self.counters.hit(false);
+                return Ok(None);
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        if file.len() < 32 || Sha256::digest(&file[32..]).as_slice() != &file[..32] {
+            warn!("cached blob {} is corrupt; dropping it", blob_id);
+            self.forget(blob_id);
+            let _ = tokio::fs::remove_file(&path).await;
+            self.counters.hit(false);
+            return Ok(None);
+        }
+        self.index.lock().unwrap().0.promote(blob_id);
+        // the mtime is what `open` rebuilds LRU order from
+        if let Err(e) = touch(&path).await {
+            debug!("could not refresh mtime of cached blob {}: {}", blob_id, e);
+        }
+        self.counters.hit(true);
+        Ok(Some(file[32..].to_vec()))
+    }
+
+    async fn put(&self, blob_id: &str, data: &[u8]) -> Result<(), SyscoinError> {
+        if !is_cache_key(blob_id) {
+            return Err(format!("blob id {:?} cannot be used as a cache key", blob_id).into());
+        }
+        let size = data.len() as u64;
+        if size > self.max_bytes {
+            return Ok(());
+        }
+
+        let path = self.path_for(blob_id);
+        tokio::fs::create_dir_all(path.parent().expect("sharded path")).await?;
+        let mut file = Vec::with_capacity(32 + data.len());
+        file.extend_from_slice(&Sha256::digest(data));
+        file.extend_from_slice(data);
+        // write then rename so readers never see a partial file
+        let tmp = path.with_extension("tmp");
+        tokio::fs::write(&tmp, &file).await?;
+        tokio::fs::rename(&tmp, &path).await?;
+
+        {
+            let mut guard = self.index.lock().unwrap();
+            let (lru, bytes) = &mut *guard;
+            if let Some(old) = lru.put(blob_id.to_owned(), size) {
+                *bytes -= old;
+            }
+            *bytes += size;
+        }
+        self.counters.insertions.fetch_add(1, Ordering::Relaxed);
+        self.evict_over_limit().await;
+        Ok(())
+    }
+
+    fn stats(&self) -> CacheStats {
+        let guard = self.index.lock().unwrap();
+        self.counters.snapshot(guard.0.len(), guard.1)
+    }
+}
+
+/// Blob ids are hex hashes; anything else could escape the cache directory
+pub(crate) fn is_cache_key(id: &str) -> bool {
+    id.len() >= 2 && id.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+}
+
+/// Compare a locally computed versionhash with the requested one; a mismatch
+/// only fails when `check` is on
+pub(crate) fn versionhash_accepted(check: bool, versionhash: &str, computed: &str) -> bool {
+    if computed == cache_key(versionhash) {
+        return true;
+    }
+    if !check {
+        warn!("cloud copy of {} hashes to {}; versionhash checks are off", versionhash, computed);
+    }
+    !check
+}
+
+/// Normalise a blob id for use as a cache key
+pub(crate) fn cache_key(blob_id: &str) -> String {
+    blob_id.strip_prefix("0x").unwrap_or(blob_id).to_ascii_lowercase()
+}
+
+impl SyscoinClient {
+    /// Consult `cache` in `get_blob` before the node and PoDA cloud.
+    /// Blobs served by the node are cached; cloud fallbacks only when a
+    /// verifier or the versionhash check accepts them.
+    pub fn with_blob_cache(mut self, cache: Arc<dyn BlobCache>) -> Self {
+        self.blob_cache = Some(cache);
+        self
+    }
+
+    /// Check blobs fetched from PoDA cloud with `verifier` before using or caching them
+    pub fn with_blob_verifier(mut self, verifier: BlobVerifier) -> Self {
+        self.blob_verifier = Some(verifier);
+        self
+    }
+
+    /// Reject PoDA cloud answers that do not hash to the requested versionhash
+    /// with `blob_versionhash`. Off by default: until that formula is pinned to
+    /// a node-issued versionhash, a mismatch is only logged.
+    pub fn with_versionhash_check(mut self, enabled: bool) -> Self {
+        self.check_versionhash = enabled;
+        self
+    }
+
+    /// Whether cloud `data` may be returned as the blob `versionhash` names:
+    /// the versionhash check (if enabled) and the verifier (if set) accept it
+    pub(crate) fn accepts_blob(&self, versionhash: &str, data: &[u8]) -> bool {
+        let key = cache_key(versionhash);
+        versionhash_accepted(self.check_versionhash, &key, &blob_versionhash(data))
+            && self.blob_verifier.as_ref().is_none_or(|verify| verify(&key, data))
+    }
+
+    /// Whether cloud `data` is verified well enough to keep: `accepts_blob`
+    /// holds and at least one of its checks is configured
+    pub(crate) fn verify_blob(&self, versionhash: &str, data: &[u8]) -> bool {
+        (self.check_versionhash || self.blob_verifier.is_some()) && self.accepts_blob(versionhash, data)
+    }
+
+    pub fn blob_cache(&self) -> Option<&Arc<dyn BlobCache>> {
+        self.blob_cache.as_ref()
+    }
+
+    pub(crate) async fn cached_blob(&self, blob_id: &str) -> Option<Vec<u8>> {
+        let cache = self.blob_cache.as_ref()?;
+        match cache.get(&cache_key(blob_id)).await {
+            Ok(found) => found,
+            Err(e) => {
+                warn!("blob cache lookup for {} failed: {}", blob_id, e);
+                None
+            }
+        }
+    }
+
+    /// Cache `data` for `blob_id`; `from_node` marks data served by the trusted node
+    pub(crate) async fn cache_blob(&self, blob_id: &str, data: &[u8], from_node: bool) {
+        let Some(cache) = &self.blob_cache else {
+            return;
+        };
+        let key = cache_key(blob_id);
+        if !from_node && !self.verify_blob(&key, data) {
+            debug!("not caching unverified cloud copy of {}", blob_id);
+            return;
+        }
+        if let Err(e) = cache.put(&key, data).await {
+            warn!("caching blob {} failed: {}", blob_id, e);
+        }
+    }
+}
+
+async fn touch(path: &Path) -> std::io::Result<()> {
+    let file = tokio::fs::OpenOptions::new().write(true).open(path).await?;
+    file.into_std().await.set_modified(SystemTime::now())
+}
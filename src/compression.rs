@@ -0,0 +1,226 @@
+#[cfg(any(feature = "zstd", feature = "brotli", feature = "lz4"))]
+use std::io::Read;
+
+use tracing::{debug, info};
+
+use crate::envelope::{decode_envelope, encode_envelope_with_codec, Codec, DecodedBlob, EnvelopeOptions};
+use crate::{SyscoinClient, SyscoinError};
+
+/// Payloads smaller than this rarely shrink enough to pay for the envelope header
+const DEFAULT_MIN_SIZE: usize = 256;
+/// Default ceiling on decompressed output, guarding against decompression bombs
+const DEFAULT_MAX_DECOMPRESSED_SIZE: usize = 64 * 1024 * 1024;
+
+/// Compression applied by `create_blob` when configured with `with_compression`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompressionConfig {
+    pub codec: Codec,
+    /// Codec-specific level; `None` uses the codec default (ignored by lz4)
+    pub level: Option<i32>,
+    /// Payloads shorter than this are stored uncompressed
+    pub min_size: usize,
+    /// Refuse to decompress blobs that would expand beyond this many bytes
+    pub max_decompressed_size: usize,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            codec: Codec::None,
+            level: None,
+            min_size: DEFAULT_MIN_SIZE,
+            max_decompressed_size: DEFAULT_MAX_DECOMPRESSED_SIZE,
+        }
+    }
+}
+
+impl CompressionConfig {
+    /// Compress with `codec` at its default level
+    pub fn new(codec: Codec) -> Self {
+        Self { codec, ..Default::default() }
+    }
+}
+
+/// Sizes of a submitted blob before and after compression
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompressionReport {
+    /// `Codec::None` when the payload was stored as is
+    pub codec: Codec,
+    pub original_size: usize,
+    /// Bytes actually stored on chain, including any envelope header
+    pub stored_size: usize,
+}
+
+impl CompressionReport {
+    /// Stored size relative to the original, e.g. `0.25` for a 4x reduction
+    pub fn ratio(&self) -> f64 {
+        if self.original_size == 0 {
+            return 1.0;
+        }
+        self.stored_size as f64 / self.original_size as f64
+    }
+}
+
+/// Outcome of `create_blob_with_report`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlobSubmission {
+    pub versionhash: String,
+    pub compression: CompressionReport,
+}
+
+impl SyscoinClient {
+    /// Compress blob payloads with `config` whenever that makes them smaller.
+    /// Compressed blobs are wrapped in an envelope so `get_blob` can undo it.
+    pub fn with_compression(mut self, config: CompressionConfig) -> Self {
+        self.compression = Some(config);
+        self
+    }
+
+    pub fn compression(&self) -> Option<&CompressionConfig> {
+        self.compression.as_ref()
+    }
+
+    /// Like `create_blob`, but also reports how much compression saved
+    pub async fn create_blob_with_report(&self, data: &[u8]) -> Result<BlobSubmission, SyscoinError> {
+        let (blob, compression) = self.prepare_blob(data, None)?;
+        let versionhash = self.submit_blob_bytes(&blob).await?;
+        Ok(BlobSubmission { versionhash, compression })
+    }
+
+    /// Build the bytes to store for `data`: compressed inside an envelope when the
+    /// configured codec saves space, otherwise raw (or a plain envelope if `envelope` is set).
+    pub(crate) fn prepare_blob(
+        &self,
+        data: &[u8],
+        envelope: Option<&EnvelopeOptions>,
+    ) -> Result<(Vec<u8>, CompressionReport), SyscoinError> {
+        let plain = match envelope {
+            Some(options) => encode_envelope_with_codec(data, data.len() as u64, Codec::None, options)?,
+            None => data.to_vec(),
+        };
+        let mut report = CompressionReport { codec: Codec::None, original_size: data.len(), stored_size: plain.len() };
+
+        let Some(config) = self.compression.filter(|c| c.codec != Codec::None && data.len() >= c.min_size) else {
+            return Ok((plain, report));
+        };
+
+        let compressed = compress(config.codec, config.level, data)?;
+        let default_options = EnvelopeOptions::default();
+        let candidate = encode_envelope_with_codec(
+            &compressed,
+            data.len() as u64,
+            config.codec,
+            envelope.unwrap_or(&default_options),
+        )?;
+        if candidate.len() >= plain.len() {
+            debug!("{:?} did not shrink {} byte blob; storing uncompressed", config.codec, data.len());
+            return Ok((plain, report));
+        }
+
+        report.codec = config.codec;
+        report.stored_size = candidate.len();
+        info!(
+            "compressed blob with {:?}: {} -> {} bytes ({:.0}%)",
+            config.codec,
+            report.original_size,
+            report.stored_size,
+            report.ratio() * 100.0
+        );
+        Ok((candidate, report))
+    }
+
+    /// Unpack stored blob bytes, decompressing the payload if its envelope names a codec
+    pub(crate) fn decode_stored_blob(&self, blob: &[u8]) -> Result<DecodedBlob, SyscoinError> {
+        let mut decoded = decode_envelope(blob);
+        let Some(header) = &decoded.header else {
+            return Ok(decoded);
+        };
+        if header.codec == Codec::None {
+            return Ok(decoded);
+        }
+
+        let limit = self
+            .compression
+            .map(|c| c.max_decompressed_size)
+            .unwrap_or(DEFAULT_MAX_DECOMPRESSED_SIZE);
+        let expected = usize::try_from(header.original_len).unwrap_or(usize::MAX);
+        if expected > limit {
+            return Err(format!(
+                "blob claims {} decompressed bytes, above the {} byte limit",
+                header.original_len, limit
+            )
+            .into());
+        }
+
+        let data = decompress(header.codec, &decoded.data, expected)?;
+        if data.len() != expected {
+            return Err(format!(
+                "decompressed blob is {} bytes but its envelope declares {}",
+                data.len(),
+                expected
+            )
+            .into());
+        }
+        decoded.data = data;
+        Ok(decoded)
+    }
+}
+
+#[cfg_attr(not(any(feature = "zstd", feature = "brotli")), allow(unused_variables))]
+fn compress(codec: Codec, level: Option<i32>, data: &[u8]) -> Result<Vec<u8>, SyscoinError> {
+    match codec {
+        Codec::None => Ok(data.to_vec()),
+        #[cfg(feature = "zstd")]
+        Codec::Zstd => Ok(zstd::bulk::compress(data, level.unwrap_or(zstd::DEFAULT_COMPRESSION_LEVEL))?),
+        #[cfg(feature = "brotli")]
+        Codec::Brotli => {
+            use std::io::Write;
+
+            let quality = level.unwrap_or(9).clamp(0, 11) as u32;
+            let mut out = Vec::new();
+            {
+                let mut writer = brotli::CompressorWriter::new(&mut out, 4096, quality, 22);
+                writer.write_all(data)?;
+            }
+            Ok(out)
+        }
+        #[cfg(feature = "lz4")]
+        Codec::Lz4 => {
+            use std::io::Write;
+
+            let mut encoder = lz4_flex::frame::FrameEncoder::new(Vec::new());
+            encoder.write_all(data)?;
+            Ok(encoder.finish()?)
+        }
+        #[cfg(not(all(feature = "zstd", feature = "brotli", feature = "lz4")))]
+        other => Err(missing_feature(other)),
+    }
+}
+
+/// Decompress at most `limit` bytes; anything beyond is cut off and caught by the caller's length check
+#[cfg_attr(not(any(feature = "zstd", feature = "brotli", feature = "lz4")), allow(unused_variables))]
+fn decompress(codec: Codec, data: &[u8], limit: usize) -> Result<Vec<u8>, SyscoinError> {
+    match codec {
+        Codec::None => Ok(data.to_vec()),
+        #[cfg(feature = "zstd")]
+        Codec::Zstd => read_limited(zstd::stream::read::Decoder::new(data)?, data.len(), limit),
+        #[cfg(feature = "brotli")]
+        Codec::Brotli => read_limited(brotli::Decompressor::new(data, 4096), data.len(), limit),
+        #[cfg(feature = "lz4")]
+        Codec::Lz4 => read_limited(lz4_flex::frame::FrameDecoder::new(data), data.len(), limit),
+        #[cfg(not(all(feature = "zstd", feature = "brotli", feature = "lz4")))]
+        other => Err(missing_feature(other)),
+    }
+}
+
+#[cfg(any(feature = "zstd", feature = "brotli", feature = "lz4"))]
+fn read_limited(reader: impl Read, input_len: usize, limit: usize) -> Result<Vec<u8>, SyscoinError> {
+    let mut out = Vec::with_capacity(limit.min(input_len.saturating_mul(4)));
+    reader.take(limit as u64 + 1).read_to_end(&mut out)?;
+    Ok(out)
+}
+
+#[cfg(not(all(feature = "zstd", feature = "brotli", feature = "lz4")))]
+fn missing_feature(codec: Codec) -> SyscoinError {
+    format!("{:?} support is not enabled; rebuild with the `{}` feature", codec, codec.feature_name()).into()
+}
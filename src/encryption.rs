@@ -0,0 +1,237 @@
+//! Client-side encryption of blob payloads (feature `encryption`).
+//!
+//! Layout:
+//!
+//! ```text
+//! magic "SDAX" | version u8 | mode u8 | nonce [24]
+//! | mode 1 only: ephemeral public key [32] | recipient count u16
+//! |              count × (wrap nonce [24] + wrapped content key [48])
+//! | XChaCha20-Poly1305 ciphertext (header bytes above as associated data)
+//! ```
+//!
+//! Mode 0 encrypts with a shared key directly. Mode 1 encrypts with a random
+//! content key that is wrapped once per X25519 recipient, so readers only need
+//! their own secret key.
+
+use std::fmt;
+
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use hkdf::Hkdf;
+use sha2::Sha256;
+use x25519_dalek::{PublicKey, StaticSecret};
+
+use crate::{SyscoinClient, SyscoinError, MAX_BLOB_SIZE};
+
+pub const ENCRYPTED_MAGIC: &[u8; 4] = b"SDAX";
+const ENCRYPTED_VERSION: u8 = 1;
+const MODE_SHARED: u8 = 0;
+const MODE_RECIPIENTS: u8 = 1;
+const NONCE_LEN: usize = 24;
+const TAG_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+const WRAPPED_KEY_LEN: usize = NONCE_LEN + KEY_LEN + TAG_LEN;
+const WRAP_INFO: &[u8] = b"syscoin-da blob key wrap v1";
+
+/// A 256-bit symmetric blob key
+#[derive(Clone, PartialEq, Eq)]
+pub struct BlobKey([u8; KEY_LEN]);
+
+impl BlobKey {
+    /// A fresh random key from the OS RNG
+    pub fn generate() -> Self {
+        Self(XChaCha20Poly1305::generate_key(&mut OsRng).into())
+    }
+
+    pub fn from_bytes(bytes: [u8; KEY_LEN]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; KEY_LEN] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for BlobKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("BlobKey(..)")
+    }
+}
+
+/// Who can read an encrypted blob
+#[derive(Debug, Clone)]
+pub enum EncryptionKey {
+    /// Everyone holding the same symmetric key
+    Shared(BlobKey),
+    /// Holders of the secret keys matching these X25519 public keys
+    Recipients(Vec<PublicKey>),
+}
+
+/// Key used to open an encrypted blob
+#[derive(Clone)]
+pub enum DecryptionKey {
+    Shared(BlobKey),
+    /// The reader's X25519 secret key
+    Recipient(StaticSecret),
+}
+
+impl fmt::Debug for DecryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecryptionKey::Shared(_) => f.write_str("DecryptionKey::Shared(..)"),
+            DecryptionKey::Recipient(_) => f.write_str("DecryptionKey::Recipient(..)"),
+        }
+    }
+}
+
+/// Bytes added on top of the plaintext by encrypting for `recipients` readers
+/// (`0` for a shared key). A payload fits in a blob when
+/// `len + encryption_overhead(n) <= MAX_BLOB_SIZE`.
+pub fn encryption_overhead(recipients: usize) -> usize {
+    let header = ENCRYPTED_MAGIC.len() + 2 + NONCE_LEN;
+    let wrapping = if recipients == 0 { 0 } else { KEY_LEN + 2 + recipients * WRAPPED_KEY_LEN };
+    header + wrapping + TAG_LEN
+}
+
+impl SyscoinClient {
+    /// Encrypt `data` client-side and publish the ciphertext.
+    /// The configured compression, if any, is applied before encrypting.
+    pub async fn create_encrypted_blob(&self, data: &[u8], key: &EncryptionKey) -> Result<String, SyscoinError> {
+        let (plaintext, _) = self.prepare_blob(data, None)?;
+        let blob = encrypt_blob(&plaintext, key)?;
+        self.submit_blob_bytes(&blob).await
+    }
+
+    /// Fetch an encrypted blob and decrypt it with `key`
+    pub async fn get_decrypted_blob(&self, blob_id: &str, key: &DecryptionKey) -> Result<Vec<u8>, SyscoinError> {
+        let blob = self.fetch_blob_bytes(blob_id).await?;
+        let plaintext = decrypt_blob(&blob, key)?;
+        self.payload_of(plaintext)
+    }
+}
+
+/// Encrypt `plaintext` into the blob layout described in the module docs
+pub fn encrypt_blob(plaintext: &[u8], key: &EncryptionKey) -> Result<Vec<u8>, SyscoinError> {
+    let recipients = match key {
+        EncryptionKey::Shared(_) => 0,
+        EncryptionKey::Recipients(list) if list.is_empty() => return Err("no recipients to encrypt for".into()),
+        EncryptionKey::Recipients(list) => list.len(),
+    };
+    let size = plaintext.len() + encryption_overhead(recipients);
+    if size > MAX_BLOB_SIZE {
+        return Err(format!("encrypted blob size ({}) exceeds maximum allowed ({})", size, MAX_BLOB_SIZE).into());
+    }
+    let recipient_count = u16::try_from(recipients).map_err(|_| "too many recipients")?;
+
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let mut header = Vec::with_capacity(size - plaintext.len() - TAG_LEN);
+    header.extend_from_slice(ENCRYPTED_MAGIC);
+    header.push(ENCRYPTED_VERSION);
+
+    let content_key = match key {
+        EncryptionKey::Shared(shared) => {
+            header.push(MODE_SHARED);
+            header.extend_from_slice(&nonce);
+            shared.clone()
+        }
+        EncryptionKey::Recipients(list) => {
+            header.push(MODE_RECIPIENTS);
+            header.extend_from_slice(&nonce);
+
+            let content_key = BlobKey::generate();
+            // one ephemeral key per blob, used against every recipient (hence not `EphemeralSecret`,
+            // whose Diffie-Hellman consumes it)
+            let ephemeral = StaticSecret::random_from_rng(OsRng);
+            let ephemeral_public = PublicKey::from(&ephemeral);
+            header.extend_from_slice(ephemeral_public.as_bytes());
+            header.extend_from_slice(&recipient_count.to_le_bytes());
+
+            for recipient in list {
+                let shared = ephemeral.diffie_hellman(recipient);
+                let wrap_key = derive_wrap_key(shared.as_bytes(), &ephemeral_public, recipient);
+                let wrap_nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+                let wrapped = XChaCha20Poly1305::new(wrap_key.as_bytes().into())
+                    .encrypt(&wrap_nonce, content_key.as_bytes().as_slice())
+                    .map_err(|_| "failed to wrap blob key")?;
+                header.extend_from_slice(&wrap_nonce);
+                header.extend_from_slice(&wrapped);
+            }
+            content_key
+        }
+    };
+
+    let ciphertext = XChaCha20Poly1305::new(content_key.as_bytes().into())
+        .encrypt(&nonce, Payload { msg: plaintext, aad: &header })
+        .map_err(|_| "failed to encrypt blob")?;
+    header.extend_from_slice(&ciphertext);
+    Ok(header)
+}
+
+/// Decrypt a blob produced by `encrypt_blob`
+pub fn decrypt_blob(blob: &[u8], key: &DecryptionKey) -> Result<Vec<u8>, SyscoinError> {
+    if !blob.starts_with(ENCRYPTED_MAGIC) {
+        return Err("blob is not encrypted".into());
+    }
+    let truncated = || -> SyscoinError { "encrypted blob is truncated".into() };
+    let fixed = ENCRYPTED_MAGIC.len() + 2 + NONCE_LEN;
+    if blob.len() < fixed + TAG_LEN {
+        return Err(truncated());
+    }
+    let version = blob[4];
+    if version != ENCRYPTED_VERSION {
+        return Err(format!("unsupported encrypted blob version {}", version).into());
+    }
+    let mode = blob[5];
+    let nonce = XNonce::from_slice(&blob[6..fixed]);
+
+    let (content_key, header_len) = match (mode, key) {
+        (MODE_SHARED, DecryptionKey::Shared(shared)) => (shared.clone(), fixed),
+        (MODE_RECIPIENTS, DecryptionKey::Recipient(secret)) => {
+            let ephemeral_public: [u8; KEY_LEN] = blob
+                .get(fixed..fixed + KEY_LEN)
+                .ok_or_else(truncated)?
+                .try_into()
+                .expect("32 bytes");
+            let ephemeral_public = PublicKey::from(ephemeral_public);
+            let count_at = fixed + KEY_LEN;
+            let count = blob.get(count_at..count_at + 2).ok_or_else(truncated)?;
+            let count = u16::from_le_bytes([count[0], count[1]]) as usize;
+            let entries_at = count_at + 2;
+            let header_len = entries_at + count * WRAPPED_KEY_LEN;
+            let entries = blob.get(entries_at..header_len).ok_or_else(truncated)?;
+
+            let own_public = PublicKey::from(secret);
+            let shared = secret.diffie_hellman(&ephemeral_public);
+            let wrap_key = derive_wrap_key(shared.as_bytes(), &ephemeral_public, &own_public);
+            let cipher = XChaCha20Poly1305::new(wrap_key.as_bytes().into());
+            let content_key = entries
+                .chunks_exact(WRAPPED_KEY_LEN)
+                .find_map(|entry| {
+                    let (wrap_nonce, wrapped) = entry.split_at(NONCE_LEN);
+                    cipher.decrypt(XNonce::from_slice(wrap_nonce), wrapped).ok()
+                })
+                .ok_or("blob is not encrypted for this key")?;
+            let content_key: [u8; KEY_LEN] = content_key.try_into().map_err(|_| "malformed wrapped key")?;
+            (BlobKey::from_bytes(content_key), header_len)
+        }
+        (MODE_SHARED, DecryptionKey::Recipient(_)) => return Err("blob was encrypted with a shared key".into()),
+        (MODE_RECIPIENTS, DecryptionKey::Shared(_)) => return Err("blob was encrypted for X25519 recipients".into()),
+        (other, _) => return Err(format!("unknown encryption mode {}", other).into()),
+    };
+
+    let (header, ciphertext) = blob.split_at(header_len);
+    XChaCha20Poly1305::new(content_key.as_bytes().into())
+        .decrypt(nonce, Payload { msg: ciphertext, aad: header })
+        .map_err(|_| "failed to decrypt blob: wrong key or tampered ciphertext".into())
+}
+
+fn derive_wrap_key(shared: &[u8; KEY_LEN], ephemeral_public: &PublicKey, recipient: &PublicKey) -> BlobKey {
+    let mut salt = [0u8; 2 * KEY_LEN];
+    salt[..KEY_LEN].copy_from_slice(ephemeral_public.as_bytes());
+    salt[KEY_LEN..].copy_from_slice(recipient.as_bytes());
+    let mut okm = [0u8; KEY_LEN];
+    Hkdf::<Sha256>::new(Some(&salt), shared)
+        .expand(WRAP_INFO, &mut okm)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    BlobKey(okm)
+}
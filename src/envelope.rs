@@ -0,0 +1,213 @@
+//! Optional self-describing wrapper around blob payloads.
+//!
+//! Layout (integers little-endian):
+//!
+//! ```text
+//! magic "SDAE" | version u8 | codec u8 | original_len u64
+//! | content_type (u16 len + utf8) | namespace (u16 len + utf8)
+//! | metadata count u16 | count × (key u16 len + utf8, value u16 len + utf8)
+//! | payload
+//! ```
+//!
+//! Blobs that do not start with the magic (or whose header is malformed) are
+//! treated as raw bytes, so blobs published before envelopes stay readable.
+
+use std::collections::BTreeMap;
+
+use tracing::debug;
+
+use crate::{SyscoinClient, SyscoinError};
+
+pub const ENVELOPE_MAGIC: &[u8; 4] = b"SDAE";
+pub const ENVELOPE_VERSION: u8 = 1;
+
+/// How the envelope payload is encoded.
+/// Compressing codecs need the matching cargo feature to read or write blobs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Codec {
+    #[default]
+    None,
+    Zstd,
+    Brotli,
+    Lz4,
+}
+
+impl Codec {
+    fn id(self) -> u8 {
+        match self {
+            Codec::None => 0,
+            Codec::Zstd => 1,
+            Codec::Brotli => 2,
+            Codec::Lz4 => 3,
+        }
+    }
+
+    fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Codec::None),
+            1 => Some(Codec::Zstd),
+            2 => Some(Codec::Brotli),
+            3 => Some(Codec::Lz4),
+            _ => None,
+        }
+    }
+
+    /// Cargo feature providing this codec
+    #[cfg(not(all(feature = "zstd", feature = "brotli", feature = "lz4")))]
+    pub(crate) fn feature_name(self) -> &'static str {
+        match self {
+            Codec::None => "",
+            Codec::Zstd => "zstd",
+            Codec::Brotli => "brotli",
+            Codec::Lz4 => "lz4",
+        }
+    }
+}
+
+/// Descriptive fields written into a new envelope
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EnvelopeOptions {
+    /// MIME type of the payload, e.g. `application/json`
+    pub content_type: Option<String>,
+    /// Application namespace so unrelated producers can share a wallet
+    pub namespace: Option<String>,
+    pub metadata: BTreeMap<String, String>,
+}
+
+/// Header parsed from an enveloped blob
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvelopeHeader {
+    pub version: u8,
+    pub codec: Codec,
+    /// Payload length before any codec was applied
+    pub original_len: u64,
+    pub content_type: Option<String>,
+    pub namespace: Option<String>,
+    pub metadata: BTreeMap<String, String>,
+}
+
+/// Blob contents with the envelope (if any) unpacked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedBlob {
+    /// `None` for raw blobs without an envelope
+    pub header: Option<EnvelopeHeader>,
+    pub data: Vec<u8>,
+}
+
+/// Wrap `payload` in an envelope
+pub fn encode_envelope(payload: &[u8], options: &EnvelopeOptions) -> Result<Vec<u8>, SyscoinError> {
+    encode_envelope_with_codec(payload, payload.len() as u64, Codec::None, options)
+}
+
+pub(crate) fn encode_envelope_with_codec(
+    payload: &[u8],
+    original_len: u64,
+    codec: Codec,
+    options: &EnvelopeOptions,
+) -> Result<Vec<u8>, SyscoinError> {
+    let mut out = Vec::with_capacity(payload.len() + 64);
+    out.extend_from_slice(ENVELOPE_MAGIC);
+    out.push(ENVELOPE_VERSION);
+    out.push(codec.id());
+    out.extend_from_slice(&original_len.to_le_bytes());
+    write_str(&mut out, options.content_type.as_deref().unwrap_or(""))?;
+    write_str(&mut out, options.namespace.as_deref().unwrap_or(""))?;
+
+    let count = u16::try_from(options.metadata.len()).map_err(|_| "too many envelope metadata entries")?;
+    out.extend_from_slice(&count.to_le_bytes());
+    for (key, value) in &options.metadata {
+        write_str(&mut out, key)?;
+        write_str(&mut out, value)?;
+    }
+
+    out.extend_from_slice(payload);
+    Ok(out)
+}
+
+/// Split a blob into its envelope header and payload.
+/// The payload is returned as stored, without undoing any compression.
+pub fn decode_envelope(blob: &[u8]) -> DecodedBlob {
+    match parse_header(blob) {
+        Some((header, offset)) => DecodedBlob { header: Some(header), data: blob[offset..].to_vec() },
+        None => DecodedBlob { header: None, data: blob.to_vec() },
+    }
+}
+
+fn write_str(out: &mut Vec<u8>, s: &str) -> Result<(), SyscoinError> {
+    let len = u16::try_from(s.len()).map_err(|_| format!("envelope field too long ({} bytes)", s.len()))?;
+    out.extend_from_slice(&len.to_le_bytes());
+    out.extend_from_slice(s.as_bytes());
+    Ok(())
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        let bytes = self.buf.get(self.pos..self.pos.checked_add(n)?)?;
+        self.pos += n;
+        Some(bytes)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|b| b[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.take(2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.take(8).map(|b| u64::from_le_bytes(b.try_into().expect("8 bytes")))
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let len = self.u16()? as usize;
+        String::from_utf8(self.take(len)?.to_vec()).ok()
+    }
+}
+
+fn parse_header(blob: &[u8]) -> Option<(EnvelopeHeader, usize)> {
+    if !blob.starts_with(ENVELOPE_MAGIC) {
+        return None;
+    }
+    let mut r = Reader { buf: blob, pos: ENVELOPE_MAGIC.len() };
+    let version = r.u8()?;
+    if version != ENVELOPE_VERSION {
+        debug!("unsupported envelope version {}; treating blob as raw", version);
+        return None;
+    }
+    let codec = Codec::from_id(r.u8()?)?;
+    let original_len = r.u64()?;
+    let content_type = Some(r.string()?).filter(|s| !s.is_empty());
+    let namespace = Some(r.string()?).filter(|s| !s.is_empty());
+    let count = r.u16()?;
+    let mut metadata = BTreeMap::new();
+    for _ in 0..count {
+        let key = r.string()?;
+        let value = r.string()?;
+        metadata.insert(key, value);
+    }
+
+    let header = EnvelopeHeader { version, codec, original_len, content_type, namespace, metadata };
+    Some((header, r.pos))
+}
+
+impl SyscoinClient {
+    /// Wrap `data` in a versioned envelope and publish it, compressing the payload
+    /// if the client is configured to
+    pub async fn create_blob_with_envelope(&self, data: &[u8], options: &EnvelopeOptions) -> Result<String, SyscoinError> {
+        let (blob, _) = self.prepare_blob(data, Some(options))?;
+        self.submit_blob_bytes(&blob).await
+    }
+
+    /// Fetch a blob and unpack its envelope; raw blobs come back with `header: None`
+    pub async fn get_blob_decoded(&self, blob_id: &str) -> Result<DecodedBlob, SyscoinError> {
+        let blob = self.fetch_blob_bytes(blob_id).await?;
+        self.decode_stored_blob(&blob)
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+
+use reed_solomon_erasure::galois_8::ReedSolomon;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+
+use crate::envelope::EnvelopeOptions;
+use crate::{SyscoinClient, SyscoinError, MAX_BLOB_SIZE};
+
+/// Content type marking descriptor blobs
+pub const ERASURE_DESCRIPTOR_CONTENT_TYPE: &str = "application/vnd.syscoin-da.erasure+json";
+const DESCRIPTOR_VERSION: u8 = 1;
+/// GF(2^8) Reed-Solomon supports at most this many shards in total
+const MAX_TOTAL_SHARDS: usize = 256;
+
+/// Shape of the Reed-Solomon code: any `data_shards` of the
+/// `data_shards + parity_shards` blobs are enough to rebuild the payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErasureConfig {
+    pub data_shards: usize,
+    pub parity_shards: usize,
+}
+
+impl Default for ErasureConfig {
+    fn default() -> Self {
+        Self { data_shards: 4, parity_shards: 2 }
+    }
+}
+
+/// Contents of the descriptor blob, listing every shard of an erasure-coded payload
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErasureDescriptor {
+    pub version: u8,
+    pub data_shards: usize,
+    pub parity_shards: usize,
+    /// Length of every shard in bytes
+    pub shard_size: usize,
+    /// Payload length before padding to whole shards
+    pub original_len: usize,
+    /// Hex SHA-256 of the payload
+    pub sha256: String,
+    /// Blob ids of the shards, data shards first
+    pub shard_ids: Vec<String>,
+    /// Hex SHA-256 of each shard, in the same order
+    pub shard_hashes: Vec<String>,
+}
+
+/// Outcome of `create_erasure_coded`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErasureCodedBlob {
+    pub descriptor_id: String,
+    pub descriptor: ErasureDescriptor,
+}
+
+impl SyscoinClient {
+    /// Split `data` into data and parity shards, publish each shard as its own blob,
+    /// then publish a descriptor blob listing them. Keep the descriptor id:
+    /// it is all `recover_erasure_coded` needs.
+    pub async fn create_erasure_coded(
+        &self,
+        data: &[u8],
+        config: &ErasureConfig,
+    ) -> Result<ErasureCodedBlob, SyscoinError> {
+        let (k, m) = (config.data_shards, config.parity_shards);
+        if k == 0 || m == 0 || k + m > MAX_TOTAL_SHARDS {
+            return Err(format!(
+                "erasure coding needs at least one data and one parity shard and at most {} in total",
+                MAX_TOTAL_SHARDS
+            )
+            .into());
+        }
+        let shard_size = data.len().div_ceil(k).max(1);
+        if shard_size > MAX_BLOB_SIZE {
+            return Err(format!(
+                "payload of {} bytes needs shards of {} bytes, above the blob limit ({}); use more data shards",
+                data.len(),
+                shard_size,
+                MAX_BLOB_SIZE
+            )
+            .into());
+        }
+
+        let mut shards: Vec<Vec<u8>> = data.chunks(shard_size).map(|c| c.to_vec()).collect();
+        shards.resize(k, Vec::new());
+        shards.resize(k + m, Vec::new());
+        for shard in &mut shards {
+            shard.resize(shard_size, 0);
+        }
+        ReedSolomon::new(k, m)?.encode(&mut shards)?;
+
+        // Short or zero-padded payloads repeat shards; a repeat reuses the first copy's
+        // blob instead of resubmitting it before that one is final
+        let shard_hashes: Vec<String> = shards.iter().map(|s| sha256_hex(s)).collect();
+        let mut published: HashMap<&str, String> = HashMap::new();
+        let mut shard_ids = Vec::with_capacity(k + m);
+        for (i, (shard, hash)) in shards.iter().zip(&shard_hashes).enumerate() {
+            if let Some(id) = published.get(hash.as_str()) {
+                shard_ids.push(id.clone());
+                continue;
+            }
+            let id = self.create_blob(shard).await.map_err(|e| {
+                format!("uploading shard {} of {} failed after {} succeeded: {}", i + 1, k + m, shard_ids.len(), e)
+            })?;
+            published.insert(hash, id.clone());
+            shard_ids.push(id);
+        }
+
+        let descriptor = ErasureDescriptor {
+            version: DESCRIPTOR_VERSION,
+            data_shards: k,
+            parity_shards: m,
+            shard_size,
+            original_len: data.len(),
+            sha256: sha256_hex(data),
+            shard_ids,
+            shard_hashes,
+        };
+        let options = EnvelopeOptions {
+            content_type: Some(ERASURE_DESCRIPTOR_CONTENT_TYPE.to_owned()),
+            ..Default::default()
+        };
+        let descriptor_id = self
+            .create_blob_with_envelope(&serde_json::to_vec(&descriptor)?, &options)
+            .await?;
+        info!("published {}+{} erasure-coded shards, descriptor {}", k, m, descriptor_id);
+        Ok(ErasureCodedBlob { descriptor_id, descriptor })
+    }
+
+    /// Read the descriptor blob written by `create_erasure_coded`
+    pub async fn get_erasure_descriptor(&self, descriptor_id: &str) -> Result<ErasureDescriptor, SyscoinError> {
+        let decoded = self.get_blob_decoded(descriptor_id).await?;
+        let is_descriptor = decoded
+            .header
+            .as_ref()
+            .and_then(|h| h.content_type.as_deref())
+            == Some(ERASURE_DESCRIPTOR_CONTENT_TYPE);
+        if !is_descriptor {
+            return Err(format!("blob {} is not an erasure coding descriptor", descriptor_id).into());
+        }
+        let descriptor: ErasureDescriptor = serde_json::from_slice(&decoded.data)?;
+        let total = descriptor.data_shards + descriptor.parity_shards;
+        if descriptor.version != DESCRIPTOR_VERSION
+            || descriptor.shard_ids.len() != total
+            || descriptor.shard_hashes.len() != total
+        {
+            return Err(format!("malformed erasure coding descriptor {}", descriptor_id).into());
+        }
+        Ok(descriptor)
+    }
+
+    /// Rebuild an erasure-coded payload from any `data_shards` intact shards.
+    /// Data shards are fetched first, so parity is only read when something is missing.
+    pub async fn recover_erasure_coded(&self, descriptor_id: &str) -> Result<Vec<u8>, SyscoinError> {
+        let descriptor = self.get_erasure_descriptor(descriptor_id).await?;
+        let k = descriptor.data_shards;
+
+        let mut shards: Vec<Option<Vec<u8>>> = vec![None; descriptor.shard_ids.len()];
+        let mut intact = 0;
+        for (i, id) in descriptor.shard_ids.iter().enumerate() {
+            if intact == k {
+                break;
+            }
+            match self.get_blob(id).await {
+                Ok(shard) if shard.len() == descriptor.shard_size && sha256_hex(&shard) == descriptor.shard_hashes[i] => {
+                    shards[i] = Some(shard);
+                    intact += 1;
+                }
+                Ok(_) => warn!("shard {} ({}) is corrupt; ignoring it", i, id),
+                Err(e) => warn!("shard {} ({}) is unavailable: {}", i, id, e),
+            }
+        }
+        if intact < k {
+            return Err(format!(
+                "only {} of {} shards are retrievable; {} are needed",
+                intact,
+                descriptor.shard_ids.len(),
+                k
+            )
+            .into());
+        }
+
+        if shards[..k].iter().any(Option::is_none) {
+            ReedSolomon::new(k, descriptor.parity_shards)?.reconstruct_data(&mut shards)?;
+        }
+        let mut data: Vec<u8> = shards
+            .into_iter()
+            .take(k)
+            .flat_map(|s| s.expect("data shards are reconstructed"))
+            .collect();
+        data.truncate(descriptor.original_len);
+
+        if sha256_hex(&data) != descriptor.sha256 {
+            return Err("recovered payload does not match the descriptor checksum".into());
+        }
+        Ok(data)
+    }
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
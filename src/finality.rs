@@ -0,0 +1,230 @@
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+use tokio::time::sleep;
+use tracing::{info, warn};
+
+use crate::{rpc_error_code, BitcoinDaFinalityMode, SyscoinClient, SyscoinError, RPC_WALLET_ERROR};
+
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Outcome of `bump_blob_fee`
+#[derive(Debug, Clone, PartialEq)]
+pub struct BumpFeeResult {
+    /// Carrier transaction that was replaced
+    pub original_txid: String,
+    /// Replacement transaction, when the wallet could sign and broadcast it
+    pub txid: Option<String>,
+    /// Unsigned replacement PSBT, for wallets with private keys disabled
+    pub psbt: Option<String>,
+    /// Fee of the original transaction in SYS
+    pub original_fee: f64,
+    /// Fee of the replacement transaction in SYS
+    pub fee: f64,
+}
+
+/// Automatic fee bumping for `wait_for_blob_finality`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeeBumpPolicy {
+    /// Bump once the carrier transaction has been pending for this many blocks
+    pub after_blocks: u64,
+    /// Fee rate in sat/vB for the first bump; `None` lets the wallet pick one
+    pub fee_rate: Option<f64>,
+    /// Factor applied to `fee_rate` for each further bump
+    pub fee_rate_multiplier: f64,
+    /// Never bump above this fee rate (sat/vB)
+    pub max_fee_rate: Option<f64>,
+    pub max_bumps: u32,
+}
+
+impl Default for FeeBumpPolicy {
+    fn default() -> Self {
+        Self {
+            after_blocks: 3,
+            fee_rate: None,
+            fee_rate_multiplier: 1.5,
+            max_fee_rate: None,
+            max_bumps: 3,
+        }
+    }
+}
+
+/// Settings for `wait_for_blob_finality`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FinalityWaitOptions {
+    pub mode: BitcoinDaFinalityMode,
+    /// Required confirmations in `Confirmations` mode
+    pub confirmations: u64,
+    pub poll_interval: Duration,
+    /// Give up after this long; `None` waits indefinitely
+    pub timeout: Option<Duration>,
+    /// Bump the carrier transaction's fee when it stays unconfirmed
+    pub fee_bump: Option<FeeBumpPolicy>,
+}
+
+impl Default for FinalityWaitOptions {
+    fn default() -> Self {
+        Self {
+            mode: BitcoinDaFinalityMode::Chainlock,
+            confirmations: 1,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            timeout: None,
+            fee_bump: None,
+        }
+    }
+}
+
+/// Result of a successful `wait_for_blob_finality`
+#[derive(Debug, Clone, PartialEq)]
+pub struct FinalityOutcome {
+    /// Carrier transaction that made it on chain (the last replacement if bumped)
+    pub txid: Option<String>,
+    pub fee_bumps: u32,
+}
+
+/// Progress of automatic fee bumping within one `wait_for_blob_finality` call
+struct BumpState {
+    policy: FeeBumpPolicy,
+    next_fee_rate: Option<f64>,
+    /// Block height at which the current carrier transaction was first seen pending
+    pending_since: Option<u64>,
+}
+
+impl BumpState {
+    fn new(policy: FeeBumpPolicy) -> Self {
+        Self { policy, next_fee_rate: policy.fee_rate, pending_since: None }
+    }
+}
+
+impl SyscoinClient {
+    /// Bump the fee of the transaction carrying `blob_id`.
+    /// `new_fee_rate` is in sat/vB; `None` lets the wallet pick one.
+    pub async fn bump_blob_fee(&self, blob_id: &str, new_fee_rate: Option<f64>) -> Result<BumpFeeResult, SyscoinError> {
+        let info = self
+            .lookup_blob(blob_id)
+            .await?
+            .ok_or_else(|| format!("blob {} is not known to the node", blob_id))?;
+        let txid = info
+            .get("txid")
+            .and_then(|v| v.as_str())
+            .ok_or("Missing txid in getnevmblobdata response")?;
+        self.bump_transaction_fee(txid, new_fee_rate).await
+    }
+
+    /// Poll until `blob_id` is final, bumping the carrier fee per `options.fee_bump`
+    pub async fn wait_for_blob_finality(
+        &self,
+        blob_id: &str,
+        options: &FinalityWaitOptions,
+    ) -> Result<FinalityOutcome, SyscoinError> {
+        let started = Instant::now();
+        let mut outcome = FinalityOutcome { txid: None, fee_bumps: 0 };
+        let mut fee_bump = options.fee_bump.map(BumpState::new);
+
+        loop {
+            if self
+                .check_blob_finality_with_mode(blob_id, options.mode, options.confirmations)
+                .await?
+            {
+                if outcome.txid.is_none() {
+                    outcome.txid = self.blob_txid(blob_id).await?;
+                }
+                return Ok(outcome);
+            }
+
+            if let Some(state) = fee_bump.as_mut().filter(|s| outcome.fee_bumps < s.policy.max_bumps) {
+                if !self.bump_if_stuck(blob_id, state, &mut outcome).await? {
+                    fee_bump = None;
+                }
+            }
+
+            if options.timeout.is_some_and(|t| started.elapsed() >= t) {
+                return Err(format!("timed out waiting for finality of blob {}", blob_id).into());
+            }
+            sleep(options.poll_interval).await;
+        }
+    }
+
+    /// Bump the carrier fee once it has been pending for `after_blocks`.
+    /// Returns `false` when the wallet cannot sign replacements, so bumping should stop.
+    async fn bump_if_stuck(
+        &self,
+        blob_id: &str,
+        state: &mut BumpState,
+        outcome: &mut FinalityOutcome,
+    ) -> Result<bool, SyscoinError> {
+        let Some(info) = self.lookup_blob(blob_id).await? else {
+            return Ok(true);
+        };
+        if outcome.txid.is_none() {
+            outcome.txid = info.get("txid").and_then(|v| v.as_str()).map(|s| s.to_owned());
+        }
+        if info.get("height").and_then(|v| v.as_u64()).is_some() {
+            state.pending_since = None;
+            return Ok(true);
+        }
+
+        let height = self.get_block_count().await?;
+        let since = *state.pending_since.get_or_insert(height);
+        let Some(txid) = outcome.txid.clone() else {
+            return Ok(true);
+        };
+        if height.saturating_sub(since) < state.policy.after_blocks {
+            return Ok(true);
+        }
+
+        let rate = match (state.next_fee_rate, state.policy.max_fee_rate) {
+            (Some(rate), Some(max)) => Some(rate.min(max)),
+            (rate, _) => rate,
+        };
+        info!("blob {} pending for {} blocks; bumping fee of {}", blob_id, height.saturating_sub(since), txid);
+        state.pending_since = Some(height);
+        match self.bump_transaction_fee(&txid, rate).await {
+            Ok(BumpFeeResult { txid: Some(replacement), .. }) => {
+                outcome.txid = Some(replacement);
+                outcome.fee_bumps += 1;
+                state.next_fee_rate = rate.map(|r| r * state.policy.fee_rate_multiplier);
+            }
+            Ok(_) => {
+                warn!("wallet cannot sign fee bumps for {}; disabling automatic bumping", txid);
+                return Ok(false);
+            }
+            Err(e) => warn!("fee bump of {} failed: {}", txid, e),
+        }
+        Ok(true)
+    }
+
+    async fn blob_txid(&self, blob_id: &str) -> Result<Option<String>, SyscoinError> {
+        Ok(self
+            .lookup_blob(blob_id)
+            .await?
+            .and_then(|info| info.get("txid").and_then(|v| v.as_str()).map(|s| s.to_owned())))
+    }
+
+    /// `bumpfee`, falling back to `psbtbumpfee` for wallets that cannot sign
+    async fn bump_transaction_fee(&self, txid: &str, fee_rate: Option<f64>) -> Result<BumpFeeResult, SyscoinError> {
+        let mut options = json!({});
+        if let Some(rate) = fee_rate {
+            options["fee_rate"] = json!(rate);
+        }
+        let params = [json!(txid), options];
+
+        let resp = match self.call_wallet_unlocked("bumpfee", &params).await {
+            Ok(v) => v,
+            Err(e) if rpc_error_code(&e) == Some(RPC_WALLET_ERROR) && e.to_string().contains("psbtbumpfee") => {
+                info!("wallet cannot sign; creating replacement PSBT for {}", txid);
+                self.call_wallet_unlocked("psbtbumpfee", &params).await?
+            }
+            Err(e) => return Err(e),
+        };
+
+        let amount = |key: &str| resp.get(key).and_then(Value::as_f64).unwrap_or_default();
+        Ok(BumpFeeResult {
+            original_txid: txid.to_owned(),
+            txid: resp.get("txid").and_then(|v| v.as_str()).map(|s| s.to_owned()),
+            psbt: resp.get("psbt").and_then(|v| v.as_str()).map(|s| s.to_owned()),
+            original_fee: amount("origfee"),
+            fee: amount("fee"),
+        })
+    }
+}
@@ -0,0 +1,101 @@
+use serde_json::Value;
+use tokio::sync::Mutex;
+use tracing::{debug, info, warn};
+
+use crate::{blob_versionhash, SyscoinClient, SyscoinError, MAX_BLOB_SIZE};
+
+/// Options for `create_blob_with_options`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CreateBlobOptions {
+    /// Passed to `syscoincreatenevmblob`; also skips the duplicate check
+    pub overwrite_existing: bool,
+    /// Look the blob up first and skip submission if it is already final
+    pub skip_if_final: bool,
+}
+
+impl Default for CreateBlobOptions {
+    fn default() -> Self {
+        Self { overwrite_existing: false, skip_if_final: true }
+    }
+}
+
+/// Outcome of `create_blob_with_options`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlobCreation {
+    /// A new blob transaction was submitted
+    Created(String),
+    /// Identical data is already published and final; nothing was paid
+    AlreadyExists(String),
+}
+
+impl BlobCreation {
+    pub fn versionhash(&self) -> &str {
+        match self {
+            BlobCreation::Created(hash) | BlobCreation::AlreadyExists(hash) => hash,
+        }
+    }
+
+    pub fn already_existed(&self) -> bool {
+        matches!(self, BlobCreation::AlreadyExists(_))
+    }
+}
+
+impl SyscoinClient {
+    /// Like `create_blob`, but says whether the blob was already published and
+    /// lets the caller force a resubmission with `overwrite_existing`
+    pub async fn create_blob_with_options(
+        &self,
+        data: &[u8],
+        options: &CreateBlobOptions,
+    ) -> Result<BlobCreation, SyscoinError> {
+        let (blob, _) = self.prepare_blob(data, None)?;
+        self.submit_blob_bytes_with(&blob, options, None).await
+    }
+
+    /// Submit `blob` as stored, unless `options` allow reusing a final copy.
+    /// `funding`, when given, is held around the submission itself only.
+    pub(crate) async fn submit_blob_bytes_with(
+        &self,
+        blob: &[u8],
+        options: &CreateBlobOptions,
+        funding: Option<&Mutex<()>>,
+    ) -> Result<BlobCreation, SyscoinError> {
+        if blob.len() > MAX_BLOB_SIZE {
+            return Err(format!("blob size ({}) exceeds maximum allowed ({})", blob.len(), MAX_BLOB_SIZE).into());
+        }
+        let local_hash = blob_versionhash(blob);
+        if options.skip_if_final && !options.overwrite_existing && self.is_final_on_node(&local_hash).await {
+            info!("blob {} is already published and final; not resubmitting", local_hash);
+            self.store_blob(&local_hash, blob, true).await;
+            return Ok(BlobCreation::AlreadyExists(local_hash));
+        }
+
+        let versionhash = {
+            let _funding = match funding {
+                Some(lock) => Some(lock.lock().await),
+                None => None,
+            };
+            self.send_blob(blob, options.overwrite_existing).await?
+        };
+        if versionhash.strip_prefix("0x").unwrap_or(&versionhash) != local_hash {
+            warn!(
+                "node versionhash {} differs from locally computed {}; duplicate checks will miss this blob",
+                versionhash, local_hash
+            );
+        }
+        self.store_blob(&versionhash, blob, true).await;
+        Ok(BlobCreation::Created(versionhash))
+    }
+
+    /// Lookup failures only cost a possible resubmission, so they count as "not final"
+    async fn is_final_on_node(&self, versionhash: &str) -> bool {
+        match self.lookup_blob(versionhash).await {
+            Ok(Some(info)) => info.get("chainlock").and_then(Value::as_bool).unwrap_or(false),
+            Ok(None) => false,
+            Err(e) => {
+                debug!("duplicate check for {} failed: {}", versionhash, e);
+                false
+            }
+        }
+    }
+}
@@ -0,0 +1,303 @@
+use std::collections::{BTreeMap, HashMap};
+use std::ops::RangeBounds;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::time::sleep;
+use tracing::{debug, info, warn};
+
+use crate::cache::cache_key;
+use crate::{rpc_error_code, RpcClient, SyscoinClient, SyscoinError};
+
+/// Transaction version Syscoin uses for NEVM blob carriers
+pub const NEVM_DATA_TX_VERSION: u32 = 137;
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(30);
+const DEFAULT_BLOCKS_PER_POLL: usize = 100;
+/// `gettransaction` error for transactions that are not in the wallet
+const RPC_INVALID_ADDRESS_OR_KEY: i64 = -5;
+
+/// Which blobs `ChainIndexer` records
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IndexScope {
+    /// Every blob on chain
+    #[default]
+    All,
+    /// Only blobs whose carrier transaction belongs to the configured wallet
+    Wallet,
+}
+
+/// Settings for `ChainIndexer`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexerConfig {
+    /// First height to index when the index is empty
+    pub start_height: u64,
+    pub scope: IndexScope,
+    /// Transaction versions treated as blob carriers; each one costs a `getnevmblobdata`
+    pub blob_tx_versions: Vec<u32>,
+    /// Most blocks walked by one `poll`
+    pub blocks_per_poll: usize,
+    /// Delay between polls in `follow` once the tip is reached
+    pub poll_interval: Duration,
+}
+
+impl Default for IndexerConfig {
+    fn default() -> Self {
+        Self {
+            start_height: 0,
+            scope: IndexScope::All,
+            blob_tx_versions: vec![NEVM_DATA_TX_VERSION],
+            blocks_per_poll: DEFAULT_BLOCKS_PER_POLL,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+}
+
+/// A blob found on chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexedBlob {
+    pub versionhash: String,
+    pub txid: String,
+    pub height: u64,
+    pub block_hash: String,
+    /// `None` when the node does not know the blob
+    pub size: Option<u64>,
+}
+
+/// Blobs found on chain, by height. Serialize it to resume indexing later.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlobIndex {
+    /// Hash of every indexed block, for reorg detection
+    blocks: BTreeMap<u64, String>,
+    blobs: BTreeMap<u64, Vec<IndexedBlob>>,
+    #[serde(skip)]
+    by_hash: HashMap<String, u64>,
+}
+
+impl BlobIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Highest indexed block and its hash
+    pub fn tip(&self) -> Option<(u64, &str)> {
+        self.blocks.iter().next_back().map(|(h, hash)| (*h, hash.as_str()))
+    }
+
+    pub fn get(&self, versionhash: &str) -> Option<&IndexedBlob> {
+        let key = cache_key(versionhash);
+        let height = match self.by_hash.get(&key) {
+            Some(height) => *height,
+            // indexes loaded with serde have no lookup table yet
+            None => return self.iter().find(|b| b.versionhash == key),
+        };
+        self.blobs.get(&height)?.iter().find(|b| b.versionhash == key)
+    }
+
+    pub fn by_txid(&self, txid: &str) -> Option<&IndexedBlob> {
+        self.iter().find(|b| b.txid == txid)
+    }
+
+    /// Blobs in blocks within `heights`, in chain order
+    pub fn in_heights(&self, heights: impl RangeBounds<u64>) -> impl Iterator<Item = &IndexedBlob> {
+        self.blobs.range(heights).flat_map(|(_, blobs)| blobs)
+    }
+
+    /// All blobs in chain order
+    pub fn iter(&self) -> impl Iterator<Item = &IndexedBlob> {
+        self.blobs.values().flatten()
+    }
+
+    pub fn len(&self) -> usize {
+        self.blobs.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blobs.is_empty()
+    }
+
+    fn insert_block(&mut self, height: u64, block_hash: String, blobs: Vec<IndexedBlob>) {
+        self.blocks.insert(height, block_hash);
+        if !blobs.is_empty() {
+            for blob in &blobs {
+                self.by_hash.insert(blob.versionhash.clone(), height);
+            }
+            self.blobs.insert(height, blobs);
+        }
+    }
+
+    /// Forget `height` and every block above it, returning the blobs they held
+    fn rollback_from(&mut self, height: u64) -> Vec<IndexedBlob> {
+        self.blocks.split_off(&height);
+        let removed: Vec<IndexedBlob> = self.blobs.split_off(&height).into_values().flatten().collect();
+        for blob in &removed {
+            self.by_hash.remove(&blob.versionhash);
+        }
+        removed
+    }
+}
+
+/// What one `ChainIndexer::poll` changed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IndexUpdate {
+    pub added: Vec<IndexedBlob>,
+    /// Blobs dropped because their blocks left the active chain
+    pub rolled_back: Vec<IndexedBlob>,
+    /// Highest indexed height after the poll
+    pub indexed_height: Option<u64>,
+    /// The chain tip was reached
+    pub caught_up: bool,
+}
+
+/// Walks blocks with `getblockhash`/`getblock` and records blob carrier
+/// transactions in a `BlobIndex`, rolling back blocks that a reorg replaced.
+///
+/// Call `poll` from your own scheduler, or `follow` to keep up with the chain.
+pub struct ChainIndexer<'a> {
+    client: &'a SyscoinClient,
+    config: IndexerConfig,
+    index: BlobIndex,
+}
+
+impl<'a> ChainIndexer<'a> {
+    pub fn new(client: &'a SyscoinClient, config: IndexerConfig) -> Self {
+        Self { client, config, index: BlobIndex::new() }
+    }
+
+    /// Continue from a previously saved index
+    pub fn with_index(mut self, index: BlobIndex) -> Self {
+        self.index = index;
+        self
+    }
+
+    pub fn index(&self) -> &BlobIndex {
+        &self.index
+    }
+
+    pub fn into_index(self) -> BlobIndex {
+        self.index
+    }
+
+    /// Index up to `blocks_per_poll` new blocks
+    pub async fn poll(&mut self) -> Result<IndexUpdate, SyscoinError> {
+        let chain_height = self.client.get_block_count().await?;
+        let mut update = IndexUpdate::default();
+        let mut walked = 0;
+
+        while walked < self.config.blocks_per_poll {
+            let height = match self.index.tip() {
+                Some((tip, _)) => tip + 1,
+                None => self.config.start_height,
+            };
+            if height > chain_height {
+                update.caught_up = true;
+                break;
+            }
+
+            let block_hash = self.call("getblockhash", &[json!(height)]).await?;
+            let block_hash = block_hash.as_str().ok_or("getblockhash returned a non-string result")?.to_owned();
+            let block = self.call("getblock", &[json!(block_hash), json!(2)]).await?;
+
+            let previous = block.get("previousblockhash").and_then(Value::as_str);
+            if let Some(known) = height.checked_sub(1).and_then(|h| self.index.blocks.get(&h)) {
+                if previous != Some(known.as_str()) {
+                    warn!("reorg detected at height {}; rolling back block {}", height, height - 1);
+                    // every pass drops at least one block, so this ends even at height 0
+                    update.rolled_back.extend(self.index.rollback_from(height - 1));
+                    continue;
+                }
+            }
+
+            let blobs = self.blobs_in_block(&block, height, &block_hash).await?;
+            if !blobs.is_empty() {
+                info!("indexed {} blob(s) at height {}", blobs.len(), height);
+            }
+            update.added.extend(blobs.iter().cloned());
+            self.index.insert_block(height, block_hash, blobs);
+            walked += 1;
+        }
+
+        // blobs re-added on the new branch are not really gone
+        update.rolled_back.retain(|gone| !update.added.iter().any(|b| b.versionhash == gone.versionhash && b.txid == gone.txid));
+        update.indexed_height = self.index.tip().map(|(h, _)| h);
+        Ok(update)
+    }
+
+    /// Poll forever, handing every non-empty update to `on_update`; returns only on error
+    pub async fn follow<F>(&mut self, mut on_update: F) -> Result<(), SyscoinError>
+    where
+        F: FnMut(&IndexUpdate),
+    {
+        loop {
+            let update = self.poll().await?;
+            if !update.added.is_empty() || !update.rolled_back.is_empty() {
+                on_update(&update);
+            }
+            if update.caught_up {
+                sleep(self.config.poll_interval).await;
+            }
+        }
+    }
+
+    async fn call(&self, method: &str, params: &[Value]) -> Result<Value, SyscoinError> {
+        self.client.rpc_client.call(method, params).await
+    }
+
+    async fn blobs_in_block(&self, block: &Value, height: u64, block_hash: &str) -> Result<Vec<IndexedBlob>, SyscoinError> {
+        let mut blobs = Vec::new();
+        for tx in block.get("tx").and_then(Value::as_array).into_iter().flatten() {
+            let version = tx.get("version").and_then(Value::as_u64).unwrap_or_default() as u32;
+            if !self.config.blob_tx_versions.contains(&version) {
+                continue;
+            }
+            let Some(txid) = tx.get("txid").and_then(Value::as_str) else {
+                continue;
+            };
+
+            // the node knows the carrier's versionhash; the script is only a fallback
+            let info = self.client.lookup_blob(txid).await?;
+            let from_node = info.as_ref().and_then(|i| i.get("versionhash")).and_then(Value::as_str).map(cache_key);
+            let Some(versionhash) = from_node.or_else(|| blob_commitment(tx)) else {
+                debug!("tx {} at height {} has a carrier version but no blob", txid, height);
+                continue;
+            };
+            if self.config.scope == IndexScope::Wallet && !self.in_wallet(txid).await? {
+                continue;
+            }
+            let size = info.as_ref().and_then(|i| i.get("datasize")).and_then(Value::as_u64);
+            debug!("blob {} in tx {} at height {}", versionhash, txid, height);
+            blobs.push(IndexedBlob { versionhash, txid: txid.to_owned(), height, block_hash: block_hash.to_owned(), size });
+        }
+        Ok(blobs)
+    }
+
+    async fn in_wallet(&self, txid: &str) -> Result<bool, SyscoinError> {
+        match self.client.rpc_client.call_wallet("gettransaction", &[json!(txid)]).await {
+            Ok(_) => Ok(true),
+            Err(e) if rpc_error_code(&e) == Some(RPC_INVALID_ADDRESS_OR_KEY) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Versionhash committed by a carrier's `OP_RETURN` push: either the bare
+/// 32 byte hash or a serialized payload starting with the length-prefixed hash
+fn blob_commitment(tx: &Value) -> Option<String> {
+    tx.get("vout")?.as_array()?.iter().find_map(|out| {
+        let script = out.get("scriptPubKey")?;
+        if script.get("type")?.as_str()? != "nulldata" {
+            return None;
+        }
+        let mut asm = script.get("asm")?.as_str()?.split_whitespace();
+        if asm.next()? != "OP_RETURN" {
+            return None;
+        }
+        let data = asm.next()?;
+        let hash = match data.len() {
+            64 => data,
+            len if len >= 66 && data.starts_with("20") => &data[2..66],
+            _ => return None,
+        };
+        hash.bytes().all(|b| b.is_ascii_hexdigit()).then(|| hash.to_ascii_lowercase())
+    })
+}
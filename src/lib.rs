@@ -6,12 +6,83 @@ use serde::Deserialize;
 use serde_json::{json, Value};
 use tracing::{info, warn};
 
+mod address;
+mod amount;
+mod batcher;
+mod bulk;
+mod cache;
+mod compression;
+#[cfg(feature = "encryption")]
+mod encryption;
+mod envelope;
+mod erasure;
+mod finality;
+mod idempotent;
+mod indexer;
+mod metadata;
+mod mirrors;
+mod monitor;
+mod retention;
+mod retrieval;
+#[cfg(feature = "s3")]
+mod s3;
+mod send;
+mod spend;
+mod store;
+mod stream;
+mod transaction;
+mod utxo;
+mod versionhash;
+mod wallet;
+
+pub use address::{AddressPurpose, AddressType, AddressValidation, LabeledAddress};
+pub use amount::Amount;
+pub use batcher::{decode_batch, verify_item_inclusion, BlobBatcher, ItemReceipt, BATCH_MAGIC};
+pub use bulk::{BatchOptions, BatchSubmission, BatchSummary};
+pub use cache::{BlobCache, BlobVerifier, CacheStats, DiskBlobCache, MemoryBlobCache};
+pub use compression::{BlobSubmission, CompressionConfig, CompressionReport};
+#[cfg(feature = "encryption")]
+pub use encryption::{decrypt_blob, encrypt_blob, encryption_overhead, BlobKey, DecryptionKey, EncryptionKey, ENCRYPTED_MAGIC};
+#[cfg(feature = "encryption")]
+pub use x25519_dalek::{PublicKey as X25519PublicKey, StaticSecret as X25519StaticSecret};
+pub use envelope::{decode_envelope, encode_envelope, Codec, DecodedBlob, EnvelopeHeader, EnvelopeOptions, ENVELOPE_MAGIC, ENVELOPE_VERSION};
+pub use erasure::{ErasureCodedBlob, ErasureConfig, ErasureDescriptor, ERASURE_DESCRIPTOR_CONTENT_TYPE};
+pub use finality::{BumpFeeResult, FeeBumpPolicy, FinalityOutcome, FinalityWaitOptions};
+pub use idempotent::{BlobCreation, CreateBlobOptions};
+pub use indexer::{BlobIndex, ChainIndexer, IndexScope, IndexUpdate, IndexedBlob, IndexerConfig, NEVM_DATA_TX_VERSION};
+pub use metadata::BlobMetadata;
+pub use mirrors::MirrorStrategy;
+pub use monitor::{BalanceEvent, BalanceMonitor, BalanceMonitorConfig, BalanceSnapshot};
+pub use retention::{ArchiveReport, ArchiverConfig, BlobArchiver, BlobRetention, DEFAULT_BLOB_RETENTION};
+pub use retrieval::{BlobSource, RetrievalStrategy, RetrievedBlob};
+pub use send::{SendOptions, TransactionResult};
+pub use spend::{BudgetExceeded, BudgetLimit, OverBudgetAction, SpendPolicy, SpendUsage};
+#[cfg(feature = "s3")]
+pub use s3::{S3BlobStore, S3Config};
+pub use secrecy::SecretString;
+pub use store::{BlobStore, FsBlobStore, MemoryBlobStore};
+pub use stream::BlobByteStream;
+pub use transaction::{BlobCommitment, BlobTransaction, TxInput, TxOutput};
+pub use utxo::{PreflightAction, Utxo, UtxoPreflight, UtxoTransaction};
+pub use versionhash::blob_versionhash;
+pub use wallet::{BalanceBreakdown, Balances, CreateWalletOptions, WalletInfo, WalletScan};
+use spend::SpendGuard;
+use utxo::PreflightGuard;
+use wallet::AutoUnlock;
+
 // Default timeout in seconds if none is specified
 const DEFAULT_TIMEOUT_SECS: u64 = 30;
 const SATOSHIS_PER_SYS: f64 = 100_000_000.0;
 const VBYTES_PER_KVB: f64 = 1000.0;
 const NEVM_DATA_SCALE_FACTOR: f64 = 0.01;
 
+// JSON-RPC error codes reported by the node
+const RPC_WALLET_ERROR: i64 = -4;
+const RPC_WALLET_INVALID_LABEL_NAME: i64 = -11;
+const RPC_WALLET_UNLOCK_NEEDED: i64 = -13;
+const RPC_WALLET_NOT_FOUND: i64 = -18;
+const RPC_WALLET_ALREADY_LOADED: i64 = -35;
+
 /// Maximum payload accepted by the Syscoin PoDA endpoint (2 MiB).
 pub const MAX_BLOB_SIZE: usize = 2 * 1024 * 1024;
 
@@ -24,13 +95,58 @@ pub enum BitcoinDaFinalityMode {
     Confirmations,
 }
 
+/// Application-level error returned by the node in a JSON-RPC envelope.
+///
+/// Boxed into `SyscoinError`; use `rpc_error_code` to branch on the code.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+    raw: Value,
+}
+
+impl RpcError {
+    fn from_value(raw: Value) -> Self {
+        Self {
+            code: raw.get("code").and_then(|v| v.as_i64()).unwrap_or_default(),
+            message: raw.get("message").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+            raw,
+        }
+    }
+}
+
+impl std::fmt::Display for RpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RPC error: {}", self.raw)
+    }
+}
+
+impl Error for RpcError {}
+
+/// JSON-RPC error code carried by `err`, if it came from the node
+pub fn rpc_error_code(err: &SyscoinError) -> Option<i64> {
+    err.downcast_ref::<RpcError>().map(|e| e.code)
+}
+
 /// Response structure for JSON-RPC calls
 #[derive(Deserialize, Debug)]
+#[serde(bound(deserialize = "T: Deserialize<'de>"))]
 struct JsonRpcResponse<T> {
+    // Commands like `walletlock` legitimately answer `"result": null`, so keep
+    // an explicit null apart from a missing field.
+    #[serde(default, deserialize_with = "deserialize_present")]
     result: Option<T>,
     error: Option<Value>,
 }
 
+fn deserialize_present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
 /// Common trait for RPC clients to enable easy mocking
 #[async_trait]
 pub trait RpcClient {
@@ -39,13 +155,24 @@ pub trait RpcClient {
 
     async fn call_wallet(&self, method: &str, params: &[Value]) -> Result<Value, SyscoinError>;
 
-    /// Get wallet balance with optional account and watchonly parameters
+    /// Get wallet balance with optional account and watchonly parameters.
+    /// The account parameter is deprecated on the node; only `"*"` is accepted.
     async fn get_balance(&self, account: Option<&str>, include_watchonly: Option<bool>) -> Result<f64, SyscoinError>;
 
     /// Make an HTTP GET request to the specified URL
     async fn http_get(&self, url: &str) -> Result<Vec<u8>, SyscoinError>;
 }
 
+/// Build the error for a non-2xx RPC response.
+/// The node reports most RPC errors as HTTP 500 with a JSON-RPC error body, so
+/// prefer the typed `RpcError` when the body carries one.
+fn http_error(status: reqwest::StatusCode, body: &str) -> SyscoinError {
+    if let Ok(JsonRpcResponse::<Value> { error: Some(err), .. }) = serde_json::from_str(body) {
+        return Box::new(RpcError::from_value(err));
+    }
+    format!("HTTP error: {} returned body: {}", status, body).into()
+}
+
 /// Production implementation of the RPC client for Syscoin
 pub struct RealRpcClient {
     rpc_url: String,
@@ -120,17 +247,13 @@ impl RealRpcClient {
 
         // if it wasn’t a 200, include the body in our Err
         if !status.is_success() {
-            return Err(format!(
-                "HTTP error: {} returned body: {}",
-                status, body
-            ).into());
+            return Err(http_error(status, &body));
         }
 
         // now parse the JSON-RPC envelope from the text
         let jr: JsonRpcResponse<Value> = serde_json::from_str(&body)?;
         if let Some(err) = jr.error {
-            // you can pull out err["code"] and err["message"] here too
-            return Err(format!("RPC error: {}", err).into());
+            return Err(Box::new(RpcError::from_value(err)));
         }
 
         jr.result.ok_or_else(|| "missing result in JSON-RPC response".into())
@@ -168,10 +291,7 @@ impl RealRpcClient {
 
         // if it wasn’t a 200, include the body in our Err
         if !status.is_success() {
-            return Err(format!(
-                "HTTP error: {} returned body: {}",
-                status, body
-            ).into());
+            return Err(http_error(status, &body));
         }
 
         // now parse the JSON-RPC envelope
@@ -179,7 +299,7 @@ impl RealRpcClient {
 
         // if the RPC server reported an application-level error, forward it
         if let Some(err) = jr.error {
-            return Err(format!("RPC error: {}", err).into());
+            return Err(Box::new(RpcError::from_value(err)));
         }
 
         // otherwise grab the result or error out if missing
@@ -189,31 +309,42 @@ impl RealRpcClient {
 
     /// Create or load a wallet by name
     pub async fn create_or_load_wallet(&self, wallet_name: &str) -> Result<(), SyscoinError> {
-        info!("create_or_load_wallet");
-        match self.call("loadwallet", &[json!(wallet_name)]).await {
+        self.create_or_load_wallet_with_options(wallet_name, &CreateWalletOptions::default()).await
+    }
+
+    /// Load `wallet_name`, creating it with `options` if the node has no such wallet
+    pub async fn create_or_load_wallet_with_options(
+        &self,
+        wallet_name: &str,
+        options: &CreateWalletOptions,
+    ) -> Result<(), SyscoinError> {
+        info!("create_or_load_wallet {}", wallet_name);
+        let load_params = [json!(wallet_name), json!(options.load_on_startup)];
+        let err = match self.call("loadwallet", &load_params).await {
             Ok(_) => return Ok(()),
-            Err(e) => {
-                info!("wallet error");
-                let s = e.to_string();
-                info!(s);
-                // -18 = wallet not found → create it
-                if s.contains("failed") {
-                    info!("wallet not found, creating new one");
-                    self.call("createwallet", &[json!(wallet_name)]).await?;
-                    return Ok(());
-                }
-                // -4 = wallet already loaded → ignore
-                if s.contains("already loaded") {
-                    info!("wallet already loaded, continuing");
-                    return Ok(());
-                }
-                // any other error is fatal
-                return Err(e);
+            Err(e) => e,
+        };
+
+        match err.downcast_ref::<RpcError>() {
+            Some(rpc) if rpc.code == RPC_WALLET_NOT_FOUND => {
+                info!("wallet {} not found, creating it", wallet_name);
+                self.call("createwallet", &options.to_params(wallet_name)).await?;
+                Ok(())
             }
+            Some(rpc) if rpc.code == RPC_WALLET_ALREADY_LOADED => {
+                info!("wallet {} already loaded, continuing", wallet_name);
+                Ok(())
+            }
+            // nodes before the dedicated -35 code report a loaded wallet as a generic -4
+            Some(rpc) if rpc.code == RPC_WALLET_ERROR && rpc.message.contains("already loaded") => {
+                info!("wallet {} already loaded, continuing", wallet_name);
+                Ok(())
+            }
+            // any other error is fatal
+            _ => Err(err),
         }
     }
 
-
     /// Expose the configured wallet name
     pub fn wallet_name(&self) -> &str {
         &self.wallet_name
@@ -256,6 +387,18 @@ impl RpcClient for RealRpcClient {
 pub struct SyscoinClient {
     rpc_client: RealRpcClient,
     poda_url: String,
+    spend_guard: Option<SpendGuard>,
+    auto_unlock: Option<AutoUnlock>,
+    utxo_preflight: Option<PreflightGuard>,
+    compression: Option<CompressionConfig>,
+    blob_cache: Option<std::sync::Arc<dyn BlobCache>>,
+    blob_verifier: Option<BlobVerifier>,
+    check_versionhash: bool,
+    poda_mirrors: Vec<String>,
+    mirror_strategy: MirrorStrategy,
+    retrieval_strategy: RetrievalStrategy,
+    blob_retention: Duration,
+    blob_store: Option<std::sync::Arc<dyn BlobStore>>,
 }
 
 fn parse_amount_value(value: &Value) -> Result<f64, SyscoinError> {
@@ -281,11 +424,35 @@ impl SyscoinClient {
         Ok(Self {
             rpc_client,
             poda_url: poda_url.to_string(),
+            spend_guard: None,
+            auto_unlock: None,
+            utxo_preflight: None,
+            compression: None,
+            blob_cache: None,
+            blob_verifier: None,
+            check_versionhash: false,
+            poda_mirrors: Vec::new(),
+            mirror_strategy: MirrorStrategy::default(),
+            retrieval_strategy: RetrievalStrategy::default(),
+            blob_retention: DEFAULT_BLOB_RETENTION,
+            blob_store: None,
         })
     }
 
     /// Create a blob in BitcoinDA(FKA Poda) storage
     pub async fn create_blob(&self, data: &[u8]) -> Result<String, SyscoinError> {
+        Ok(self.create_blob_with_report(data).await?.versionhash)
+    }
+
+    /// Submit already prepared blob bytes as they should be stored on chain,
+    /// reusing an identical blob that is already final
+    pub(crate) async fn submit_blob_bytes(&self, data: &[u8]) -> Result<String, SyscoinError> {
+        let creation = self.submit_blob_bytes_with(data, &CreateBlobOptions::default(), None).await?;
+        Ok(creation.versionhash().to_owned())
+    }
+
+    /// Pay for and send a `syscoincreatenevmblob` for `data`
+    pub(crate) async fn send_blob(&self, data: &[u8], overwrite_existing: bool) -> Result<String, SyscoinError> {
         if data.len() > MAX_BLOB_SIZE {
             return Err(format!(
                 "blob size ({}) exceeds maximum allowed ({})",
@@ -296,11 +463,19 @@ impl SyscoinClient {
 
         let data_hex = hex::encode(data);
         // pass positional args: data hex, overwrite_existing, hash type.
-        // Keep overwrite_existing=false to make repeated calls idempotent for identical data.
-        // Force blake2s to keep blob IDs aligned with Syscoin / OS expectations.
-        let params = vec![json!(data_hex), json!(false), json!("blake2s")];
+        // Force blake2s to keep blob IDs aligned with Syscoin / OS expectations
+        // (and with `blob_versionhash`).
+        let params = vec![json!(data_hex), json!(overwrite_existing), json!("blake2s")];
 
-        let response = self.rpc_client.call_wallet("syscoincreatenevmblob", &params).await?;
+        self.run_utxo_preflight().await;
+        let reservation = self.reserve_spend(data.len()).await?;
+        let response = match self.call_wallet_unlocked("syscoincreatenevmblob", &params).await {
+            Ok(v) => v,
+            Err(e) => {
+                self.release_spend(reservation);
+                return Err(e);
+            }
+        };
         let hash = response
             .get("versionhash")
             .and_then(|v| v.as_str())
@@ -312,10 +487,7 @@ impl SyscoinClient {
     /// Ensure there is a receive address for the provided label.
     /// If none exists, a new address is created and returned.
     pub async fn ensure_address_by_label(&self, address_label: &str) -> Result<String, SyscoinError> {
-        match self.fetch_address_by_label(address_label).await? {
-            Some(existing) => Ok(existing),
-            None => self.get_new_address(address_label).await,
-        }
+        self.ensure_address_by_label_with_type(address_label, AddressType::default()).await
     }
 
     /// Ensure the wallet is created/loaded and return a labeled funding address.
@@ -331,7 +503,8 @@ impl SyscoinClient {
         self.ensure_wallet_and_address(wallet_name, address_label).await
     }
 
-    /// Get wallet balance
+    /// Get wallet balance.
+    /// Prefer `get_balances` to tell spendable funds apart from pending and immature ones.
     pub async fn get_balance(&self) -> Result<f64, SyscoinError> {
         self.rpc_client.get_balance(None, None).await
     }
@@ -373,63 +546,43 @@ impl SyscoinClient {
         Ok((sat_per_blob_byte as u128).max(1))
     }
 
-    /// Fetch a blob; tries RPC first, then falls back to PoDA cloud
+    /// Fetch a blob from the sources chosen by `with_retrieval_strategy`
+    /// (RPC first, then PoDA cloud, by default).
+    /// Blobs stored compressed are decompressed transparently.
     pub async fn get_blob(&self, blob_id: &str) -> Result<Vec<u8>, SyscoinError> {
-        match self.get_blob_from_rpc(blob_id).await {
-            Ok(data) => Ok(data),
-            Err(e) => {
-                warn!("get_blob_from_rpc failed ({e}); falling back to cloud");
-                self.get_blob_from_cloud(blob_id).await
-            }
-        }
+        let blob = self.fetch_blob_bytes(blob_id).await?;
+        self.payload_of(blob)
     }
 
-    /// Get a fresh address for a given label
-    pub async fn get_new_address(&self, address_label: &str) -> Result<String, SyscoinError> {
-        let resp = self
-            .rpc_client
-            .call_wallet("getnewaddress", &[json!(address_label)])
-            .await?;
-        resp.as_str()
-            .map(|s| s.to_owned())
-            .ok_or_else(|| "getnewaddress returned non-string".into())
+    /// Undo compression on a stored blob; other blobs are returned as stored
+    pub(crate) fn payload_of(&self, blob: Vec<u8>) -> Result<Vec<u8>, SyscoinError> {
+        let decoded = self.decode_stored_blob(&blob)?;
+        match decoded.header {
+            Some(header) if header.codec != Codec::None => Ok(decoded.data),
+            _ => Ok(blob),
+        }
     }
 
+    /// Blob bytes exactly as stored, found per the client's `RetrievalStrategy`
+    pub(crate) async fn fetch_blob_bytes(&self, blob_id: &str) -> Result<Vec<u8>, SyscoinError> {
+        Ok(self.fetch_blob_bytes_using(blob_id, self.retrieval_strategy).await?.0)
+    }
 
-    /// Fetch an existing address by label, if any
+    /// Fetch an existing address by label, if any.
+    /// Receive addresses win over others; ties go to the lowest address so repeated
+    /// calls return the same one.
     pub async fn fetch_address_by_label(
         &self,
         address_label: &str,
     ) -> Result<Option<String>, SyscoinError> {
-        // — pass the label as a bare string —
-        let resp = match self
-            .rpc_client
-            .call_wallet("getaddressesbylabel", &[json!(address_label)])
-            .await
-        {
-            Ok(v) => v,
-            Err(e) => {
-                let msg = e.to_string();
-                // if it's the "no addresses" error, swallow it as None
-                if msg.contains("\"code\":-11") {
-                    return Ok(None);
-                }
-                // otherwise re-propagate
-                return Err(e);
-            }
-        };
-
-        // parse returned map, take the first key if any
-        if let Some(map) = resp.as_object() {
-            if let Some((addr, _)) = map.iter().next() {
-                return Ok(Some(addr.clone()));
-            }
-        }
-        Ok(None)
+        let addresses = self.list_addresses_by_label(address_label).await?;
+        let chosen = addresses
+            .iter()
+            .find(|a| a.purpose == AddressPurpose::Receive)
+            .or_else(|| addresses.first());
+        Ok(chosen.map(|a| a.address.clone()))
     }
 
-
-
     /// Retrieve blob data from RPC node
     /// Retrieve blob data from RPC node
     async fn get_blob_from_rpc(&self, blob_id: &str) -> Result<Vec<u8>, SyscoinError> {
@@ -467,40 +620,44 @@ impl SyscoinClient {
 
 
 
-    /// Retrieve blob data from PODA cloud storage
+    /// Retrieve blob data from PODA cloud storage, trying mirrors per `with_poda_mirrors`
     pub async fn get_blob_from_cloud(&self, version_hash: &str) -> Result<Vec<u8>, SyscoinError> {
-        let url = format!("{}/blob/{}", self.poda_url, version_hash);
-        self.rpc_client.http_get(&url).await
+        self.fetch_from_mirrors(version_hash).await
     }
 
-    /// Check if a blob is final
-    pub async fn check_blob_finality(&self, blob_id: &str) -> Result<bool, SyscoinError> {
+    /// Look up blob information (without data) via `getnevmblobdata`.
+    /// Returns `None` while the node does not know the blob yet.
+    pub(crate) async fn lookup_blob(&self, blob_id: &str) -> Result<Option<Value>, SyscoinError> {
         // Strip any 0x prefix
-        let actual_blob_id = if let Some(stripped) = blob_id.strip_prefix("0x") {
-            stripped
-        } else {
-            blob_id
-        };
+        let actual_blob_id = blob_id.strip_prefix("0x").unwrap_or(blob_id);
 
-        // Use positional parameter: (versionhash_or_txid: String)
-        let params = vec![json!(actual_blob_id)];
+        // Use positional parameters: (versionhash_or_txid: String, getdata: bool);
+        // say `false` explicitly rather than rely on the node's default
+        let params = vec![json!(actual_blob_id), json!(false)];
 
         // If the node does not know the blob yet, it may return an HTTP 500 with
         // a JSON-RPC error body like:
         // {"result":null,"error":{"code":-32602,"message":"Could not find blob information for versionhash ..."},"id":1}
-        // Treat this as "not final yet" instead of a hard error so that the
-        // dispatcher keeps polling for finality.
-        let response = match self.rpc_client.call("getnevmblobdata", &params).await {
-            Ok(v) => v,
+        // Treat this as "unknown yet" instead of a hard error so that callers
+        // polling for finality keep polling.
+        match self.rpc_client.call("getnevmblobdata", &params).await {
+            Ok(v) => Ok(Some(v)),
             Err(e) => {
                 let msg = e.to_string();
                 if msg.contains("Could not find blob information for versionhash")
                     || msg.contains("\"code\":-32602")
                 {
-                    return Ok(false);
+                    return Ok(None);
                 }
-                return Err(e);
+                Err(e)
             }
+        }
+    }
+
+    /// Check if a blob is final
+    pub async fn check_blob_finality(&self, blob_id: &str) -> Result<bool, SyscoinError> {
+        let Some(response) = self.lookup_blob(blob_id).await? else {
+            return Ok(false);
         };
 
         // Extract finality status from response
@@ -532,20 +689,8 @@ impl SyscoinClient {
         blob_id: &str,
         confirmations: u64,
     ) -> Result<bool, SyscoinError> {
-        let actual_blob_id = blob_id.strip_prefix("0x").unwrap_or(blob_id);
-        let params = vec![json!(actual_blob_id)];
-
-        let response = match self.rpc_client.call("getnevmblobdata", &params).await {
-            Ok(v) => v,
-            Err(e) => {
-                let msg = e.to_string();
-                if msg.contains("Could not find blob information for versionhash")
-                    || msg.contains("\"code\":-32602")
-                {
-                    return Ok(false);
-                }
-                return Err(e);
-            }
+        let Some(response) = self.lookup_blob(blob_id).await? else {
+            return Ok(false);
         };
 
         let Some(blob_height) = response.get("height").and_then(|v| v.as_u64()) else {
@@ -554,20 +699,33 @@ impl SyscoinClient {
             return Ok(false);
         };
 
-        let current_height = self
-            .rpc_client
+        let current_height = self.get_block_count().await?;
+
+        Ok(current_height.saturating_sub(blob_height) + 1 >= confirmations)
+    }
+
+    /// Height of the node's active chain tip
+    pub async fn get_block_count(&self) -> Result<u64, SyscoinError> {
+        self.rpc_client
             .call("getblockcount", &[])
             .await?
             .as_u64()
-            .ok_or("getblockcount returned non-u64 result")?;
-
-        Ok(current_height.saturating_sub(blob_height) + 1 >= confirmations)
+            .ok_or_else(|| "getblockcount returned non-u64 result".into())
     }
 
     /// Create or load a wallet by name
     pub async fn create_or_load_wallet(&self, wallet_name: &str) -> Result<(), SyscoinError> {
         self.rpc_client.create_or_load_wallet(wallet_name).await
     }
+
+    /// Load a wallet by name, creating it with `options` if it does not exist yet
+    pub async fn create_or_load_wallet_with_options(
+        &self,
+        wallet_name: &str,
+        options: &CreateWalletOptions,
+    ) -> Result<(), SyscoinError> {
+        self.rpc_client.create_or_load_wallet_with_options(wallet_name, options).await
+    }
 }
 
 /// Mock implementation for testing
@@ -0,0 +1,109 @@
+use serde_json::Value;
+use tracing::debug;
+
+use crate::{RetrievalStrategy, SyscoinClient, SyscoinError};
+
+/// What is known about a blob without downloading its data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlobMetadata {
+    pub versionhash: String,
+    /// Payload size in bytes, from the node or the cloud's `Content-Length`
+    pub size: Option<u64>,
+    pub txid: Option<String>,
+    pub block_hash: Option<String>,
+    /// `None` while the carrier transaction is unconfirmed
+    pub height: Option<u64>,
+    /// Median time past of the carrier block, as reported by the node
+    pub mpt: Option<u64>,
+    pub chainlocked: bool,
+    /// The node knows the blob
+    pub on_node: bool,
+    /// PoDA cloud has the blob; `None` when the cloud was not asked
+    pub in_cloud: Option<bool>,
+}
+
+impl BlobMetadata {
+    fn from_node(blob_id: &str, info: &Value) -> Self {
+        let text = |key: &str| info.get(key).and_then(Value::as_str).map(str::to_owned);
+        Self {
+            versionhash: text("versionhash").unwrap_or_else(|| blob_id.strip_prefix("0x").unwrap_or(blob_id).to_owned()),
+            size: info.get("datasize").and_then(Value::as_u64),
+            txid: text("txid"),
+            block_hash: text("blockhash"),
+            height: info.get("height").and_then(Value::as_u64),
+            mpt: info.get("mpt").and_then(Value::as_u64),
+            chainlocked: info.get("chainlock").and_then(Value::as_bool).unwrap_or(false),
+            on_node: true,
+            in_cloud: None,
+        }
+    }
+}
+
+impl SyscoinClient {
+    /// Whether the blob is published, checking the sources allowed by the
+    /// client's `RetrievalStrategy` without downloading it
+    pub async fn blob_exists(&self, blob_id: &str) -> Result<bool, SyscoinError> {
+        Ok(self.get_blob_metadata(blob_id).await?.is_some())
+    }
+
+    /// Describe a blob without downloading its data: node information via
+    /// `getnevmblobdata` (without data), else a HEAD request to PoDA cloud.
+    /// `None` when no allowed source knows the blob.
+    pub async fn get_blob_metadata(&self, blob_id: &str) -> Result<Option<BlobMetadata>, SyscoinError> {
+        let strategy = self.retrieval_strategy;
+        if strategy != RetrievalStrategy::CloudOnly {
+            match self.lookup_blob(blob_id).await {
+                Ok(Some(info)) => return Ok(Some(BlobMetadata::from_node(blob_id, &info))),
+                Ok(None) => {}
+                Err(e) if strategy == RetrievalStrategy::RpcOnly => return Err(e),
+                Err(e) => debug!("node lookup of {} failed ({e}); asking the cloud", blob_id),
+            }
+        }
+        if strategy == RetrievalStrategy::RpcOnly {
+            return Ok(None);
+        }
+
+        let Some(size) = self.cloud_blob_size(blob_id).await? else {
+            return Ok(None);
+        };
+        Ok(Some(BlobMetadata {
+            versionhash: blob_id.strip_prefix("0x").unwrap_or(blob_id).to_owned(),
+            size,
+            txid: None,
+            block_hash: None,
+            height: None,
+            mpt: None,
+            chainlocked: false,
+            on_node: false,
+            in_cloud: Some(true),
+        }))
+    }
+
+    /// HEAD the blob on each PoDA mirror in turn. `Some(size)` once a mirror
+    /// has it, `None` when every reachable mirror answers 404.
+    async fn cloud_blob_size(&self, blob_id: &str) -> Result<Option<Option<u64>>, SyscoinError> {
+        let mut last_error: Option<SyscoinError> = None;
+        let mut answered = false;
+        for base in self.poda_urls() {
+            let url = format!("{}/blob/{}", base, blob_id);
+            match self.rpc_client.http_client.head(&url).timeout(self.rpc_client.timeout).send().await {
+                Ok(resp) if resp.status().is_success() => return Ok(Some(content_length(&resp))),
+                Ok(resp) if resp.status() == reqwest::StatusCode::NOT_FOUND => answered = true,
+                Ok(resp) => last_error = Some(format!("HTTP error: {} checking {}", resp.status(), url).into()),
+                Err(e) => last_error = Some(e.into()),
+            }
+        }
+        match last_error {
+            Some(e) if !answered => Err(e),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// `Response::content_length` reports the (empty) body size for HEAD, so read the header
+fn content_length(resp: &reqwest::Response) -> Option<u64> {
+    resp.headers()
+        .get(reqwest::header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok())
+}
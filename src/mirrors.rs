@@ -0,0 +1,102 @@
+use std::time::Duration;
+
+use futures_util::stream::{FuturesUnordered, StreamExt};
+use tracing::{debug, warn};
+
+use crate::{RpcClient, SyscoinClient, SyscoinError};
+
+/// How `get_blob_from_cloud` spreads a request over the PoDA mirrors
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MirrorStrategy {
+    /// Try mirrors one after another until one answers with a valid blob
+    #[default]
+    Sequential,
+    /// Start the next mirror whenever `delay` passes without a valid answer
+    /// (or as soon as the previous attempt fails)
+    Hedged { delay: Duration },
+    /// Ask every mirror at once and take the first valid answer
+    Race,
+}
+
+impl SyscoinClient {
+    /// Also fetch cloud blobs from `mirrors` (after the primary `poda_url`), using `strategy`.
+    /// Answers that do not hash to the requested versionhash, or that a verifier
+    /// set with `with_blob_verifier` rejects, are discarded, so a bad mirror
+    /// cannot win a race.
+    pub fn with_poda_mirrors<I, S>(mut self, mirrors: I, strategy: MirrorStrategy) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.poda_mirrors = mirrors.into_iter().map(Into::into).collect();
+        self.mirror_strategy = strategy;
+        self
+    }
+
+    /// Every PoDA base URL in the order mirrors are tried, primary first
+    pub fn poda_urls(&self) -> Vec<&str> {
+        std::iter::once(self.poda_url.as_str())
+            .chain(self.poda_mirrors.iter().map(String::as_str))
+            .collect()
+    }
+
+    pub(crate) async fn fetch_from_mirrors(&self, version_hash: &str) -> Result<Vec<u8>, SyscoinError> {
+        let urls = self.poda_urls();
+        let hedge = match self.mirror_strategy {
+            MirrorStrategy::Sequential => None,
+            MirrorStrategy::Hedged { delay } => Some(delay),
+            MirrorStrategy::Race => Some(Duration::ZERO),
+        };
+
+        let attempt = |base: &str| {
+            let url = format!("{}/blob/{}", base, version_hash);
+            async move {
+                let result = match self.rpc_client.http_get(&url).await {
+                    Ok(data) if !self.accepts_blob(version_hash, &data) => Err("blob failed verification".into()),
+                    other => other,
+                };
+                (url, result)
+            }
+        };
+
+        let mut pending = FuturesUnordered::new();
+        let mut next = 0;
+        let mut failures = Vec::new();
+        loop {
+            if pending.is_empty() {
+                if next == urls.len() {
+                    break;
+                }
+                pending.push(attempt(urls[next]));
+                next += 1;
+            }
+
+            let more_to_start = next < urls.len();
+            let outcome = match hedge {
+                Some(delay) if more_to_start => {
+                    tokio::select! {
+                        outcome = pending.next() => outcome,
+                        _ = tokio::time::sleep(delay) => {
+                            debug!("no answer from PoDA mirrors after {:?}; also asking {}", delay, urls[next]);
+                            pending.push(attempt(urls[next]));
+                            next += 1;
+                            continue;
+                        }
+                    }
+                }
+                _ => pending.next().await,
+            };
+
+            match outcome {
+                Some((_, Ok(data))) => return Ok(data),
+                Some((url, Err(e))) => {
+                    warn!("PoDA mirror {} failed: {}", url, e);
+                    failures.push(format!("{}: {}", url, e));
+                }
+                None => {}
+            }
+        }
+
+        Err(format!("all {} PoDA mirrors failed: {}", urls.len(), failures.join("; ")).into())
+    }
+}
@@ -0,0 +1,193 @@
+use std::time::Duration;
+
+use tokio::time::sleep;
+use tracing::{debug, warn};
+
+use crate::{Balances, SyscoinClient, SyscoinError, SATOSHIS_PER_SYS};
+
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(10);
+const DEFAULT_CONF_TARGET: u16 = 6;
+
+/// Settings for `BalanceMonitor`
+#[derive(Debug, Clone, PartialEq)]
+pub struct BalanceMonitorConfig {
+    /// Delay between polls in `wait_until`
+    pub poll_interval: Duration,
+    /// Blob size (bytes) used to estimate how many blobs the wallet can still afford
+    pub blob_size: usize,
+    /// Confirmation target passed to `get_blob_base_fee`
+    pub conf_target: u16,
+    /// Emit `LowFunds` once trusted funds (SYS) drop below this value
+    pub low_balance: Option<f64>,
+    /// Emit `LowBlobCapacity` once affordable blobs drop below this count
+    pub min_affordable_blobs: Option<u64>,
+}
+
+impl Default for BalanceMonitorConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            blob_size: crate::MAX_BLOB_SIZE,
+            conf_target: DEFAULT_CONF_TARGET,
+            low_balance: None,
+            min_affordable_blobs: None,
+        }
+    }
+}
+
+/// Wallet state observed by a single poll
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BalanceSnapshot {
+    pub balances: Balances,
+    /// Blob base fee in satoshis per blob byte; the last known fee when this
+    /// poll could not fetch it, `None` if no poll has fetched it yet
+    pub fee_per_byte: Option<u128>,
+    /// How many blobs of `blob_size` bytes the trusted balance still pays for
+    pub affordable_blobs: Option<u64>,
+}
+
+/// Change reported by `BalanceMonitor::poll`
+#[derive(Debug, Clone, PartialEq)]
+pub enum BalanceEvent {
+    /// Any of the confirmed, pending or immature balances moved
+    BalanceChanged {
+        previous: Option<Balances>,
+        current: Balances,
+    },
+    /// Trusted funds fell below `low_balance`
+    LowFunds { trusted: f64, threshold: f64 },
+    /// Trusted funds are back at or above `low_balance`
+    FundsRestored { trusted: f64, threshold: f64 },
+    /// Affordable blobs fell below `min_affordable_blobs`
+    LowBlobCapacity { affordable_blobs: u64, threshold: u64 },
+    /// Affordable blobs are back at or above `min_affordable_blobs`
+    BlobCapacityRestored { affordable_blobs: u64, threshold: u64 },
+}
+
+type Callback<'a> = Box<dyn FnMut(&BalanceEvent) + Send + 'a>;
+
+/// Polls the wallet balances and reports threshold crossings.
+///
+/// Call `poll` from your own scheduler, or `wait_until` to block until a
+/// condition on the balances holds. Registered callbacks see every event.
+pub struct BalanceMonitor<'a> {
+    client: &'a SyscoinClient,
+    config: BalanceMonitorConfig,
+    last: Option<BalanceSnapshot>,
+    callbacks: Vec<Callback<'a>>,
+}
+
+impl<'a> BalanceMonitor<'a> {
+    pub fn new(client: &'a SyscoinClient, config: BalanceMonitorConfig) -> Self {
+        Self {
+            client,
+            config,
+            last: None,
+            callbacks: Vec::new(),
+        }
+    }
+
+    /// Invoke `callback` for every event emitted by subsequent polls
+    pub fn on_event<F>(&mut self, callback: F) -> &mut Self
+    where
+        F: FnMut(&BalanceEvent) + Send + 'a,
+    {
+        self.callbacks.push(Box::new(callback));
+        self
+    }
+
+    /// The most recent snapshot, if the monitor has polled at least once
+    pub fn last_snapshot(&self) -> Option<&BalanceSnapshot> {
+        self.last.as_ref()
+    }
+
+    /// Fetch balances and fees once, returning the events triggered since the previous poll.
+    /// Only a balance failure fails the poll; a fee failure is logged and the last known fee kept.
+    pub async fn poll(&mut self) -> Result<Vec<BalanceEvent>, SyscoinError> {
+        let balances = self.client.get_balances().await?;
+        let fee_per_byte = match self.client.get_blob_base_fee(self.config.conf_target).await {
+            Ok(fee) => Some(fee),
+            Err(e) => {
+                warn!("balance monitor could not fetch the blob fee: {}", e);
+                self.last.and_then(|p| p.fee_per_byte)
+            }
+        };
+        let snapshot = BalanceSnapshot {
+            balances,
+            fee_per_byte,
+            affordable_blobs: fee_per_byte.map(|fee| affordable_blobs(balances.mine.trusted, fee, self.config.blob_size)),
+        };
+        debug!(?snapshot, "balance monitor poll");
+
+        let events = self.diff(&snapshot);
+        self.last = Some(snapshot);
+        for event in &events {
+            for callback in &mut self.callbacks {
+                callback(event);
+            }
+        }
+        Ok(events)
+    }
+
+    /// Poll every `poll_interval` until `condition` holds, returning the matching snapshot
+    pub async fn wait_until<F>(&mut self, mut condition: F) -> Result<BalanceSnapshot, SyscoinError>
+    where
+        F: FnMut(&BalanceSnapshot) -> bool,
+    {
+        loop {
+            self.poll().await?;
+            let snapshot = self.last.expect("poll stores a snapshot");
+            if condition(&snapshot) {
+                return Ok(snapshot);
+            }
+            sleep(self.config.poll_interval).await;
+        }
+    }
+
+    fn diff(&self, current: &BalanceSnapshot) -> Vec<BalanceEvent> {
+        let mut events = Vec::new();
+        let previous = self.last.as_ref();
+
+        if previous.map(|p| p.balances) != Some(current.balances) {
+            events.push(BalanceEvent::BalanceChanged {
+                previous: previous.map(|p| p.balances),
+                current: current.balances,
+            });
+        }
+
+        // A monitor with no history treats the previous state as healthy, so a
+        // wallet that starts out underfunded still raises an alert on the first poll.
+        if let Some(threshold) = self.config.low_balance {
+            let trusted = current.balances.mine.trusted;
+            let was_low = previous.is_some_and(|p| p.balances.mine.trusted < threshold);
+            let is_low = trusted < threshold;
+            if is_low && !was_low {
+                events.push(BalanceEvent::LowFunds { trusted, threshold });
+            } else if !is_low && was_low {
+                events.push(BalanceEvent::FundsRestored { trusted, threshold });
+            }
+        }
+
+        if let (Some(threshold), Some(affordable_blobs)) = (self.config.min_affordable_blobs, current.affordable_blobs) {
+            let was_low = previous.and_then(|p| p.affordable_blobs).is_some_and(|a| a < threshold);
+            let is_low = affordable_blobs < threshold;
+            if is_low && !was_low {
+                events.push(BalanceEvent::LowBlobCapacity { affordable_blobs, threshold });
+            } else if !is_low && was_low {
+                events.push(BalanceEvent::BlobCapacityRestored { affordable_blobs, threshold });
+            }
+        }
+
+        events
+    }
+}
+
+/// Number of `blob_size`-byte blobs that `trusted_sys` pays for at `fee_per_byte`
+fn affordable_blobs(trusted_sys: f64, fee_per_byte: u128, blob_size: usize) -> u64 {
+    let cost_per_blob = fee_per_byte.saturating_mul(blob_size.max(1) as u128);
+    if cost_per_blob == 0 {
+        return u64::MAX;
+    }
+    let trusted_sat = (trusted_sys.max(0.0) * SATOSHIS_PER_SYS).floor() as u128;
+    u64::try_from(trusted_sat / cost_per_blob).unwrap_or(u64::MAX)
+}
@@ -0,0 +1,182 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::Value;
+use tokio::time::sleep;
+use tracing::{debug, info, warn};
+
+use crate::cache::cache_key;
+use crate::{BlobSource, BlobStore, RpcClient, SyscoinClient, SyscoinError};
+
+/// How long nodes keep NEVM blob data unless told otherwise by `with_blob_retention`
+pub const DEFAULT_BLOB_RETENTION: Duration = Duration::from_secs(6 * 60 * 60);
+/// Syscoin's target block interval, used to estimate prune heights
+const TARGET_BLOCK_SPACING: u64 = 150;
+const DEFAULT_SAFETY_MARGIN: Duration = Duration::from_secs(60 * 60);
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Where a blob stands against the node's retention window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlobRetention {
+    /// The node knows the blob and its retention window has not passed
+    pub on_node: bool,
+    /// Median time past of the carrier block (unix seconds); `None` while unconfirmed
+    pub published_at: Option<u64>,
+    /// When the node prunes the data (unix seconds, by median time past)
+    pub prune_time: Option<u64>,
+    /// Estimated height at which `prune_time` is reached
+    pub prune_height: Option<u64>,
+    /// Time left before pruning; zero once pruned
+    pub remaining: Option<Duration>,
+}
+
+impl SyscoinClient {
+    /// Assume nodes keep blob data for `window` after the carrier block
+    /// (default `DEFAULT_BLOB_RETENTION`); match it to the node's configuration
+    pub fn with_blob_retention(mut self, window: Duration) -> Self {
+        self.blob_retention = window;
+        self
+    }
+
+    pub fn blob_retention(&self) -> Duration {
+        self.blob_retention
+    }
+
+    /// Report whether the node still holds `blob_id`'s data and when it will be pruned.
+    /// Prune times follow the chain's median time past, like the node's own pruning.
+    pub async fn get_blob_retention(&self, blob_id: &str) -> Result<BlobRetention, SyscoinError> {
+        let mut retention =
+            BlobRetention { on_node: false, published_at: None, prune_time: None, prune_height: None, remaining: None };
+        let Some(info) = self.lookup_blob(blob_id).await? else {
+            return Ok(retention);
+        };
+        retention.on_node = true;
+        let Some(published_at) = info.get("mpt").and_then(Value::as_u64) else {
+            return Ok(retention);
+        };
+
+        let tip = self.rpc_client.call("getblockchaininfo", &[]).await?;
+        let tip_height = tip.get("blocks").and_then(Value::as_u64).ok_or("Missing blocks in getblockchaininfo response")?;
+        let tip_time = tip
+            .get("mediantime")
+            .and_then(Value::as_u64)
+            .ok_or("Missing mediantime in getblockchaininfo response")?;
+
+        let prune_time = published_at + self.blob_retention.as_secs();
+        let remaining = prune_time.saturating_sub(tip_time);
+        retention.published_at = Some(published_at);
+        retention.prune_time = Some(prune_time);
+        retention.prune_height = Some(tip_height + remaining.div_ceil(TARGET_BLOCK_SPACING));
+        retention.remaining = Some(Duration::from_secs(remaining));
+        retention.on_node = remaining > 0;
+        Ok(retention)
+    }
+}
+
+/// Settings for `BlobArchiver`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArchiverConfig {
+    /// Archive a blob once its remaining retention drops to this
+    pub safety_margin: Duration,
+    /// Delay between polls in `run`
+    pub poll_interval: Duration,
+}
+
+impl Default for ArchiverConfig {
+    fn default() -> Self {
+        Self { safety_margin: DEFAULT_SAFETY_MARGIN, poll_interval: DEFAULT_POLL_INTERVAL }
+    }
+}
+
+/// What one `BlobArchiver::poll` did
+#[derive(Debug, Default)]
+pub struct ArchiveReport {
+    /// Blobs copied into the store by this poll (or found there already)
+    pub archived: Vec<String>,
+    /// Blobs still tracked because they are not close to expiry yet
+    pub pending: usize,
+    /// Blobs that could not be archived; they stay tracked
+    pub failed: Vec<(String, SyscoinError)>,
+}
+
+/// Copies tracked blobs into a `BlobStore` before the node prunes them.
+///
+/// Call `poll` from your own scheduler, or `run` to poll forever. Blobs whose
+/// retention cannot be determined, or which are already pruned, are archived
+/// right away (falling back to PoDA cloud per the client's retrieval strategy).
+/// Cloud copies are only archived once the client's verifier or versionhash
+/// check accepts them; otherwise the blob is reported as failed and stays tracked.
+pub struct BlobArchiver<'a> {
+    client: &'a SyscoinClient,
+    store: Arc<dyn BlobStore>,
+    config: ArchiverConfig,
+    tracked: BTreeSet<String>,
+}
+
+impl<'a> BlobArchiver<'a> {
+    pub fn new(client: &'a SyscoinClient, store: Arc<dyn BlobStore>, config: ArchiverConfig) -> Self {
+        Self { client, store, config, tracked: BTreeSet::new() }
+    }
+
+    /// Archive `versionhash` before it expires
+    pub fn track(&mut self, versionhash: &str) -> &mut Self {
+        self.tracked.insert(cache_key(versionhash));
+        self
+    }
+
+    /// Blobs not archived yet
+    pub fn tracked(&self) -> impl Iterator<Item = &str> {
+        self.tracked.iter().map(String::as_str)
+    }
+
+    /// Archive every tracked blob that is due, once
+    pub async fn poll(&mut self) -> ArchiveReport {
+        let mut report = ArchiveReport::default();
+        for id in self.tracked.clone() {
+            match self.archive_if_due(&id).await {
+                Ok(true) => {
+                    self.tracked.remove(&id);
+                    report.archived.push(id);
+                }
+                Ok(false) => report.pending += 1,
+                Err(e) => {
+                    warn!("archiving blob {} failed: {}", id, e);
+                    report.failed.push((id, e));
+                }
+            }
+        }
+        report
+    }
+
+    /// Poll every `poll_interval` until nothing is left to archive
+    pub async fn run(&mut self) {
+        loop {
+            let report = self.poll().await;
+            if self.tracked.is_empty() {
+                return;
+            }
+            debug!(archived = report.archived.len(), pending = report.pending, "blob archiver poll");
+            sleep(self.config.poll_interval).await;
+        }
+    }
+
+    async fn archive_if_due(&self, id: &str) -> Result<bool, SyscoinError> {
+        if self.store.contains(id).await? {
+            return Ok(true);
+        }
+        let retention = self.client.get_blob_retention(id).await?;
+        if retention.on_node && retention.remaining.is_some_and(|left| left > self.config.safety_margin) {
+            return Ok(false);
+        }
+        let (data, source) = self.client.fetch_blob_bytes_using(id, self.client.retrieval_strategy).await?;
+        // after pruning the data may come from PoDA cloud; archive it only when
+        // the client would also keep it in its own blob store
+        if source == BlobSource::Cloud && !self.client.verify_blob(id, &data) {
+            return Err(format!("cloud copy of blob {} is unverified", id).into());
+        }
+        self.store.put(id, &data).await?;
+        info!("archived blob {} ({} bytes)", id, data.len());
+        Ok(true)
+    }
+}
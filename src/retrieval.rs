@@ -0,0 +1,124 @@
+use tracing::{debug, warn};
+
+use crate::{SyscoinClient, SyscoinError};
+
+/// Where `get_blob` looks for blob data, and in what order
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RetrievalStrategy {
+    /// Only ask the node. The blob cache is not read either, so nothing that
+    /// may have come from PoDA cloud is ever returned.
+    RpcOnly,
+    /// Only ask PoDA cloud (and its mirrors)
+    CloudOnly,
+    /// Ask the node, falling back to PoDA cloud
+    #[default]
+    RpcThenCloud,
+    /// Ask PoDA cloud, falling back to the node
+    CloudThenRpc,
+    /// Ask both at once and take the first successful answer
+    Parallel,
+}
+
+/// Which source served a blob
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlobSource {
+    Cache,
+    Rpc,
+    Cloud,
+}
+
+/// Blob data together with where it came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetrievedBlob {
+    pub data: Vec<u8>,
+    pub source: BlobSource,
+}
+
+impl SyscoinClient {
+    /// Use `strategy` for `get_blob` and the other blob readers (default `RpcThenCloud`)
+    pub fn with_retrieval_strategy(mut self, strategy: RetrievalStrategy) -> Self {
+        self.retrieval_strategy = strategy;
+        self
+    }
+
+    pub fn retrieval_strategy(&self) -> RetrievalStrategy {
+        self.retrieval_strategy
+    }
+
+    /// `get_blob`, also reporting which source served the data
+    pub async fn get_blob_with_source(&self, blob_id: &str) -> Result<RetrievedBlob, SyscoinError> {
+        self.get_blob_using(blob_id, self.retrieval_strategy).await
+    }
+
+    /// `get_blob` with a one-off `strategy` instead of the client's
+    pub async fn get_blob_using(&self, blob_id: &str, strategy: RetrievalStrategy) -> Result<RetrievedBlob, SyscoinError> {
+        let (blob, source) = self.fetch_blob_bytes_using(blob_id, strategy).await?;
+        Ok(RetrievedBlob { data: self.payload_of(blob)?, source })
+    }
+
+    /// Blob bytes exactly as stored, looked up according to `strategy`
+    pub(crate) async fn fetch_blob_bytes_using(
+        &self,
+        blob_id: &str,
+        strategy: RetrievalStrategy,
+    ) -> Result<(Vec<u8>, BlobSource), SyscoinError> {
+        if strategy != RetrievalStrategy::RpcOnly {
+            if let Some(data) = self.cached_blob(blob_id).await {
+                return Ok((data, BlobSource::Cache));
+            }
+        }
+
+        let (data, source) = match strategy {
+            RetrievalStrategy::RpcOnly => (self.get_blob_from_rpc(blob_id).await?, BlobSource::Rpc),
+            RetrievalStrategy::CloudOnly => (self.get_blob_from_cloud(blob_id).await?, BlobSource::Cloud),
+            RetrievalStrategy::RpcThenCloud => match self.get_blob_from_rpc(blob_id).await {
+                Ok(data) => (data, BlobSource::Rpc),
+                Err(e) => {
+                    warn!("get_blob_from_rpc failed ({e}); falling back to cloud");
+                    (self.get_blob_from_cloud(blob_id).await?, BlobSource::Cloud)
+                }
+            },
+            RetrievalStrategy::CloudThenRpc => match self.get_blob_from_cloud(blob_id).await {
+                Ok(data) => (data, BlobSource::Cloud),
+                Err(e) => {
+                    warn!("get_blob_from_cloud failed ({e}); falling back to RPC");
+                    (self.get_blob_from_rpc(blob_id).await?, BlobSource::Rpc)
+                }
+            },
+            RetrievalStrategy::Parallel => self.fetch_from_both(blob_id).await?,
+        };
+        self.cache_blob(blob_id, &data, source == BlobSource::Rpc).await;
+        self.store_blob(blob_id, &data, source == BlobSource::Rpc).await;
+        Ok((data, source))
+    }
+
+    async fn fetch_from_both(&self, blob_id: &str) -> Result<(Vec<u8>, BlobSource), SyscoinError> {
+        let rpc = self.get_blob_from_rpc(blob_id);
+        let cloud = self.get_blob_from_cloud(blob_id);
+        tokio::pin!(rpc, cloud);
+
+        let (rpc_error, cloud_error) = tokio::select! {
+            result = &mut rpc => match result {
+                Ok(data) => return Ok((data, BlobSource::Rpc)),
+                Err(e) => {
+                    debug!("RPC lost the race for {}: {}", blob_id, e);
+                    match cloud.await {
+                        Ok(data) => return Ok((data, BlobSource::Cloud)),
+                        Err(cloud_error) => (e, cloud_error),
+                    }
+                }
+            },
+            result = &mut cloud => match result {
+                Ok(data) => return Ok((data, BlobSource::Cloud)),
+                Err(e) => {
+                    debug!("cloud lost the race for {}: {}", blob_id, e);
+                    match rpc.await {
+                        Ok(data) => return Ok((data, BlobSource::Rpc)),
+                        Err(rpc_error) => (rpc_error, e),
+                    }
+                }
+            },
+        };
+        Err(format!("blob {} unavailable: RPC: {}; cloud: {}", blob_id, rpc_error, cloud_error).into())
+    }
+}
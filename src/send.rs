@@ -0,0 +1,160 @@
+use serde_json::{json, Value};
+use tracing::{info, warn};
+
+use crate::{Amount, RpcClient, SyscoinClient, SyscoinError, Utxo};
+
+/// Options for `send_to_address` and `sweep_wallet`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SendOptions {
+    /// Fee rate in sat/vB; `None` lets the wallet estimate it
+    pub fee_rate: Option<f64>,
+    /// Deduct the fee from the sent amount instead of adding it on top
+    /// (always on for `sweep_wallet`)
+    pub subtract_fee_from_amount: bool,
+    /// Fund the transaction and report its fee without signing or broadcasting it
+    pub dry_run: bool,
+}
+
+/// Outcome of `send_to_address` or `sweep_wallet`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransactionResult {
+    /// Broadcast transaction; `None` for dry runs
+    pub txid: Option<String>,
+    pub destination: String,
+    /// Amount requested, before any fee subtraction
+    pub amount: Amount,
+    /// Fee paid, or the estimate for dry runs, when the node reports it
+    pub fee: Option<Amount>,
+    pub dry_run: bool,
+}
+
+impl SyscoinClient {
+    /// Send `amount` from the configured wallet to `address`
+    pub async fn send_to_address(
+        &self,
+        address: &str,
+        amount: Amount,
+        options: &SendOptions,
+    ) -> Result<TransactionResult, SyscoinError> {
+        let outputs = [(address.to_owned(), amount)];
+        self.send_with_options(None, &outputs, options.subtract_fee_from_amount, options)
+            .await
+    }
+
+    /// Send every spendable output of the configured wallet to `destination`,
+    /// paying the fee out of the swept amount
+    pub async fn sweep_wallet(&self, destination: &str, options: &SendOptions) -> Result<TransactionResult, SyscoinError> {
+        let inputs: Vec<Utxo> = self
+            .list_unspent(0)
+            .await?
+            .into_iter()
+            .filter(|u| u.spendable && u.safe)
+            .collect();
+        if inputs.is_empty() {
+            return Err("wallet has no spendable outputs to sweep".into());
+        }
+
+        let total: Amount = inputs.iter().map(Utxo::value).sum();
+        info!("sweeping {} outputs ({}) to {}", inputs.len(), total, destination);
+        let outputs = [(destination.to_owned(), total)];
+        self.send_with_options(Some(&inputs), &outputs, true, options).await
+    }
+
+    async fn send_with_options(
+        &self,
+        inputs: Option<&[Utxo]>,
+        outputs: &[(String, Amount)],
+        subtract_fee: bool,
+        options: &SendOptions,
+    ) -> Result<TransactionResult, SyscoinError> {
+        let (destination, amount) = outputs[0].clone();
+
+        if options.dry_run {
+            let fee = self.fund_dry_run(inputs, outputs, subtract_fee, options.fee_rate).await?;
+            info!("dry run: sending {} to {} would pay {}", amount, destination, fee);
+            return Ok(TransactionResult { txid: None, destination, amount, fee: Some(fee), dry_run: true });
+        }
+
+        let txid = self.send_outputs(inputs, outputs, subtract_fee, options.fee_rate).await?;
+        let fee = match self.rpc_client.call_wallet("gettransaction", &[json!(txid)]).await {
+            Ok(tx) => tx.get("fee").and_then(Value::as_f64).and_then(|f| Amount::from_sys(-f).ok()),
+            Err(e) => {
+                warn!("could not look up fee of {}: {}", txid, e);
+                None
+            }
+        };
+        Ok(TransactionResult { txid: Some(txid), destination, amount, fee, dry_run: false })
+    }
+
+    /// Spend to `outputs` via the `send` RPC. With `inputs`, exactly those outputs are
+    /// spent; otherwise the wallet selects coins. The fee comes out of the first
+    /// output when `subtract_fee` is set.
+    pub(crate) async fn send_outputs(
+        &self,
+        inputs: Option<&[Utxo]>,
+        outputs: &[(String, Amount)],
+        subtract_fee: bool,
+        fee_rate: Option<f64>,
+    ) -> Result<String, SyscoinError> {
+        let mut options = json!({});
+        if let Some(inputs) = inputs {
+            options["inputs"] = outpoints(inputs);
+            options["add_inputs"] = json!(false);
+        }
+        if subtract_fee {
+            options["subtract_fee_from_outputs"] = json!([0]);
+        }
+
+        // positional: outputs, conf_target, estimate_mode, fee_rate, options
+        let params = [output_list(outputs), Value::Null, Value::Null, json!(fee_rate), options];
+        let resp = self.call_wallet_unlocked("send", &params).await?;
+        resp.get("txid")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_owned())
+            .ok_or_else(|| "send returned no txid".into())
+    }
+
+    /// Fund (but neither sign nor broadcast) a transaction and return its fee
+    async fn fund_dry_run(
+        &self,
+        inputs: Option<&[Utxo]>,
+        outputs: &[(String, Amount)],
+        subtract_fee: bool,
+        fee_rate: Option<f64>,
+    ) -> Result<Amount, SyscoinError> {
+        let mut options = json!({});
+        if inputs.is_some() {
+            options["add_inputs"] = json!(false);
+        }
+        if subtract_fee {
+            options["subtractFeeFromOutputs"] = json!([0]);
+        }
+        if let Some(rate) = fee_rate {
+            options["fee_rate"] = json!(rate);
+        }
+
+        // positional: inputs, outputs, locktime, options
+        let inputs = inputs.map(outpoints).unwrap_or_else(|| json!([]));
+        let params = [inputs, output_list(outputs), json!(0), options];
+        let resp = self.rpc_client.call_wallet("walletcreatefundedpsbt", &params).await?;
+        let fee = resp
+            .get("fee")
+            .and_then(Value::as_f64)
+            .ok_or("walletcreatefundedpsbt returned no fee")?;
+        Amount::from_sys(fee)
+    }
+}
+
+fn outpoints(inputs: &[Utxo]) -> Value {
+    inputs
+        .iter()
+        .map(|u| json!({ "txid": u.txid, "vout": u.vout }))
+        .collect()
+}
+
+fn output_list(outputs: &[(String, Amount)]) -> Value {
+    outputs
+        .iter()
+        .map(|(address, amount)| json!({ address: amount.to_rpc_string() }))
+        .collect()
+}
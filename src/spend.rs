@@ -0,0 +1,265 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tokio::time::sleep;
+use tracing::{info, warn};
+
+use crate::{SyscoinClient, SyscoinError};
+
+const HOUR: Duration = Duration::from_secs(60 * 60);
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+const DEFAULT_CONF_TARGET: u16 = 6;
+
+/// What `create_blob` should do when a submission would break the spend policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverBudgetAction {
+    /// Fail immediately with a `BudgetExceeded` error
+    Fail,
+    /// Re-check fees every `poll_interval` until the submission fits.
+    /// Gives up with `BudgetExceeded` once `max_wait` has elapsed (if set).
+    WaitForFees {
+        poll_interval: Duration,
+        max_wait: Option<Duration>,
+    },
+}
+
+/// Guardrails applied to every blob submission.
+///
+/// All amounts are in satoshis, matching `SyscoinClient::get_blob_base_fee`.
+/// Limits left as `None` are not enforced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpendPolicy {
+    /// Highest acceptable blob base fee per blob byte
+    pub max_fee_per_byte: Option<u128>,
+    /// Highest acceptable estimated cost of a single blob
+    pub max_blob_cost: Option<u128>,
+    /// Total estimated spend allowed over any rolling hour
+    pub hourly_budget: Option<u128>,
+    /// Total estimated spend allowed over any rolling 24 hours
+    pub daily_budget: Option<u128>,
+    /// Confirmation target passed to `get_blob_base_fee`
+    pub conf_target: u16,
+    pub on_exceeded: OverBudgetAction,
+}
+
+impl Default for SpendPolicy {
+    fn default() -> Self {
+        Self {
+            max_fee_per_byte: None,
+            max_blob_cost: None,
+            hourly_budget: None,
+            daily_budget: None,
+            conf_target: DEFAULT_CONF_TARGET,
+            on_exceeded: OverBudgetAction::Fail,
+        }
+    }
+}
+
+/// The policy limit that rejected a submission
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetLimit {
+    FeePerByte,
+    BlobCost,
+    Hourly,
+    Daily,
+}
+
+impl fmt::Display for BudgetLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            BudgetLimit::FeePerByte => "max fee per byte",
+            BudgetLimit::BlobCost => "max blob cost",
+            BudgetLimit::Hourly => "hourly budget",
+            BudgetLimit::Daily => "daily budget",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Returned (boxed in `SyscoinError`) when a submission would exceed the spend policy.
+/// Callers can recover it with `err.downcast_ref::<BudgetExceeded>()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BudgetExceeded {
+    pub limit: BudgetLimit,
+    /// Value the submission would have reached (fee per byte, blob cost or window total)
+    pub requested: u128,
+    /// Configured ceiling for `limit`
+    pub allowed: u128,
+}
+
+impl fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "spend policy {} exceeded: {} sat requested, {} sat allowed",
+            self.limit, self.requested, self.allowed
+        )
+    }
+}
+
+impl Error for BudgetExceeded {}
+
+/// A cost booked against the rolling budgets before submission
+#[derive(Debug)]
+pub(crate) struct SpendReservation {
+    id: u64,
+    cost: u128,
+}
+
+#[derive(Default)]
+struct Ledger {
+    next_id: u64,
+    entries: VecDeque<(u64, Instant, u128)>,
+}
+
+impl Ledger {
+    fn prune(&mut self, now: Instant) {
+        while let Some((_, at, _)) = self.entries.front() {
+            if now.duration_since(*at) < DAY {
+                break;
+            }
+            self.entries.pop_front();
+        }
+    }
+
+    fn spent_since(&self, now: Instant, window: Duration) -> u128 {
+        self.entries
+            .iter()
+            .filter(|(_, at, _)| now.duration_since(*at) < window)
+            .map(|(_, _, cost)| cost)
+            .sum()
+    }
+}
+
+/// Spend policy plus the rolling record of what has been spent under it
+pub(crate) struct SpendGuard {
+    policy: SpendPolicy,
+    ledger: Mutex<Ledger>,
+}
+
+impl SpendGuard {
+    pub(crate) fn new(policy: SpendPolicy) -> Self {
+        Self {
+            policy,
+            ledger: Mutex::new(Ledger::default()),
+        }
+    }
+
+    /// Check a submission of `size` bytes at `fee_per_byte` and, if it fits,
+    /// book its estimated cost against the rolling budgets.
+    fn try_reserve(&self, fee_per_byte: u128, size: usize) -> Result<SpendReservation, BudgetExceeded> {
+        let policy = &self.policy;
+        if let Some(max) = policy.max_fee_per_byte {
+            if fee_per_byte > max {
+                return Err(BudgetExceeded { limit: BudgetLimit::FeePerByte, requested: fee_per_byte, allowed: max });
+            }
+        }
+
+        let cost = fee_per_byte.saturating_mul(size as u128);
+        if let Some(max) = policy.max_blob_cost {
+            if cost > max {
+                return Err(BudgetExceeded { limit: BudgetLimit::BlobCost, requested: cost, allowed: max });
+            }
+        }
+
+        let now = Instant::now();
+        let mut ledger = self.ledger.lock().expect("spend ledger poisoned");
+        ledger.prune(now);
+        for (limit, window, budget) in [
+            (BudgetLimit::Hourly, HOUR, policy.hourly_budget),
+            (BudgetLimit::Daily, DAY, policy.daily_budget),
+        ] {
+            let Some(budget) = budget else { continue };
+            let total = ledger.spent_since(now, window).saturating_add(cost);
+            if total > budget {
+                return Err(BudgetExceeded { limit, requested: total, allowed: budget });
+            }
+        }
+
+        let id = ledger.next_id;
+        ledger.next_id += 1;
+        ledger.entries.push_back((id, now, cost));
+        Ok(SpendReservation { id, cost })
+    }
+
+    /// Undo a reservation whose submission never reached the chain
+    fn release(&self, reservation: SpendReservation) {
+        let mut ledger = self.ledger.lock().expect("spend ledger poisoned");
+        ledger.entries.retain(|(id, _, _)| *id != reservation.id);
+    }
+
+    /// Estimated spend booked over the last hour and the last 24 hours
+    fn usage(&self) -> (u128, u128) {
+        let now = Instant::now();
+        let mut ledger = self.ledger.lock().expect("spend ledger poisoned");
+        ledger.prune(now);
+        (ledger.spent_since(now, HOUR), ledger.spent_since(now, DAY))
+    }
+}
+
+/// Estimated spend recorded by the client's spend policy, in satoshis
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpendUsage {
+    pub last_hour: u128,
+    pub last_day: u128,
+}
+
+impl SyscoinClient {
+    /// Enforce `policy` on every subsequent `create_blob` call
+    pub fn with_spend_policy(mut self, policy: SpendPolicy) -> Self {
+        self.spend_guard = Some(SpendGuard::new(policy));
+        self
+    }
+
+    /// The active spend policy, if any
+    pub fn spend_policy(&self) -> Option<&SpendPolicy> {
+        self.spend_guard.as_ref().map(|g| &g.policy)
+    }
+
+    /// Estimated spend booked under the active spend policy, if any
+    pub fn spend_usage(&self) -> Option<SpendUsage> {
+        self.spend_guard.as_ref().map(|g| {
+            let (last_hour, last_day) = g.usage();
+            SpendUsage { last_hour, last_day }
+        })
+    }
+
+    /// Return a reservation's budget after a failed submission
+    pub(crate) fn release_spend(&self, reservation: Option<SpendReservation>) {
+        if let (Some(guard), Some(reservation)) = (&self.spend_guard, reservation) {
+            guard.release(reservation);
+        }
+    }
+
+    /// Consult the spend policy before submitting `size` bytes.
+    /// Returns `None` when no policy is configured.
+    pub(crate) async fn reserve_spend(&self, size: usize) -> Result<Option<SpendReservation>, SyscoinError> {
+        let Some(guard) = &self.spend_guard else {
+            return Ok(None);
+        };
+
+        let started = Instant::now();
+        loop {
+            let fee_per_byte = self.get_blob_base_fee(guard.policy.conf_target).await?;
+            let exceeded = match guard.try_reserve(fee_per_byte, size) {
+                Ok(reservation) => {
+                    info!("spend policy: reserved {} sat for {} byte blob", reservation.cost, size);
+                    return Ok(Some(reservation));
+                }
+                Err(exceeded) => exceeded,
+            };
+
+            let OverBudgetAction::WaitForFees { poll_interval, max_wait } = guard.policy.on_exceeded else {
+                return Err(exceeded.into());
+            };
+            if max_wait.is_some_and(|max| started.elapsed() + poll_interval > max) {
+                return Err(exceeded.into());
+            }
+            warn!("{exceeded}; waiting {:?} for fees to drop", poll_interval);
+            sleep(poll_interval).await;
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use tracing::{debug, warn};
+
+use crate::cache::{cache_key, is_cache_key};
+use crate::{SyscoinClient, SyscoinError};
+
+/// Long-term storage for published blobs, keyed by versionhash.
+/// Unlike a `BlobCache`, a store never evicts on its own. Keys passed in may
+/// carry a `0x` prefix or upper case; keys handed out are normalised.
+#[async_trait]
+pub trait BlobStore: Send + Sync {
+    async fn put(&self, versionhash: &str, data: &[u8]) -> Result<(), SyscoinError>;
+
+    async fn get(&self, versionhash: &str) -> Result<Option<Vec<u8>>, SyscoinError>;
+
+    /// Every stored versionhash, sorted
+    async fn list(&self) -> Result<Vec<String>, SyscoinError>;
+
+    /// Remove a blob, returning whether it was stored
+    async fn delete(&self, versionhash: &str) -> Result<bool, SyscoinError>;
+
+    async fn contains(&self, versionhash: &str) -> Result<bool, SyscoinError> {
+        Ok(self.get(versionhash).await?.is_some())
+    }
+}
+
+/// Blob store in a directory, one file per blob at `<dir>/<hash[..2]>/<hash>`.
+/// Files hold the blob bytes exactly as published.
+pub struct FsBlobStore {
+    dir: PathBuf,
+}
+
+impl FsBlobStore {
+    /// Open (creating if needed) a store rooted at `dir`
+    pub async fn open(dir: impl AsRef<Path>) -> Result<Self, SyscoinError> {
+        let dir = dir.as_ref().to_path_buf();
+        tokio::fs::create_dir_all(&dir).await?;
+        Ok(Self { dir })
+    }
+
+    fn path_for(&self, versionhash: &str) -> Result<PathBuf, SyscoinError> {
+        let key = store_key(versionhash)?;
+        Ok(self.dir.join(&key[..2]).join(key))
+    }
+}
+
+#[async_trait]
+impl BlobStore for FsBlobStore {
+    async fn put(&self, versionhash: &str, data: &[u8]) -> Result<(), SyscoinError> {
+        let path = self.path_for(versionhash)?;
+        tokio::fs::create_dir_all(path.parent().expect("sharded path")).await?;
+        // write then rename so readers never see a partial file
+        let tmp = path.with_extension("tmp");
+        tokio::fs::write(&tmp, data).await?;
+        tokio::fs::rename(&tmp, &path).await?;
+        Ok(())
+    }
+
+    async fn get(&self, versionhash: &str) -> Result<Option<Vec<u8>>, SyscoinError> {
+        match tokio::fs::read(self.path_for(versionhash)?).await {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn list(&self) -> Result<Vec<String>, SyscoinError> {
+        let mut keys = Vec::new();
+        let mut shards = tokio::fs::read_dir(&self.dir).await?;
+        while let Some(shard) = shards.next_entry().await? {
+            if !shard.file_type().await?.is_dir() {
+                continue;
+            }
+            let mut files = tokio::fs::read_dir(shard.path()).await?;
+            while let Some(file) = files.next_entry().await? {
+                if let Some(name) = file.file_name().to_str().filter(|n| is_cache_key(n)) {
+                    keys.push(name.to_owned());
+                }
+            }
+        }
+        keys.sort();
+        Ok(keys)
+    }
+
+    async fn delete(&self, versionhash: &str) -> Result<bool, SyscoinError> {
+        match tokio::fs::remove_file(self.path_for(versionhash)?).await {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn contains(&self, versionhash: &str) -> Result<bool, SyscoinError> {
+        Ok(tokio::fs::try_exists(self.path_for(versionhash)?).await?)
+    }
+}
+
+/// Blob store held in process memory, for tests and short-lived tools
+#[derive(Default)]
+pub struct MemoryBlobStore {
+    blobs: Mutex<BTreeMap<String, Vec<u8>>>,
+}
+
+impl MemoryBlobStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl BlobStore for MemoryBlobStore {
+    async fn put(&self, versionhash: &str, data: &[u8]) -> Result<(), SyscoinError> {
+        self.blobs.lock().unwrap().insert(store_key(versionhash)?, data.to_vec());
+        Ok(())
+    }
+
+    async fn get(&self, versionhash: &str) -> Result<Option<Vec<u8>>, SyscoinError> {
+        Ok(self.blobs.lock().unwrap().get(&store_key(versionhash)?).cloned())
+    }
+
+    async fn list(&self) -> Result<Vec<String>, SyscoinError> {
+        Ok(self.blobs.lock().unwrap().keys().cloned().collect())
+    }
+
+    async fn delete(&self, versionhash: &str) -> Result<bool, SyscoinError> {
+        Ok(self.blobs.lock().unwrap().remove(&store_key(versionhash)?).is_some())
+    }
+}
+
+/// Normalise a versionhash for use as a store key, rejecting anything that is not hex
+pub(crate) fn store_key(versionhash: &str) -> Result<String, SyscoinError> {
+    let key = cache_key(versionhash);
+    if !is_cache_key(&key) {
+        return Err(format!("{:?} is not a versionhash", versionhash).into());
+    }
+    Ok(key)
+}
+
+impl SyscoinClient {
+    /// Write every blob this client publishes, and every verified blob it fetches,
+    /// through to `store`. Write failures are logged and do not fail the call.
+    pub fn with_blob_store(mut self, store: Arc<dyn BlobStore>) -> Self {
+        self.blob_store = Some(store);
+        self
+    }
+
+    pub fn blob_store(&self) -> Option<&Arc<dyn BlobStore>> {
+        self.blob_store.as_ref()
+    }
+
+    /// Copy `data` to the write-through store; `from_node` as for `cache_blob`
+    pub(crate) async fn store_blob(&self, versionhash: &str, data: &[u8], from_node: bool) {
+        let Some(store) = &self.blob_store else {
+            return;
+        };
+        if !from_node && !self.verify_blob(versionhash, data) {
+            debug!("not storing unverified cloud copy of {}", versionhash);
+            return;
+        }
+        if let Err(e) = store.put(versionhash, data).await {
+            warn!("writing blob {} to the blob store failed: {}", versionhash, e);
+        }
+    }
+}
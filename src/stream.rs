@@ -0,0 +1,421 @@
+//! Streaming variants of `create_blob` and `get_blob`.
+//!
+//! Uploads hex-encode chunk by chunk straight into the request body and
+//! downloads decode the `data` field of `getnevmblobdata` as it arrives, so
+//! neither side holds the whole payload (or its hex copy) in memory. The
+//! exceptions are an upload by a client with a blob store, which keeps a copy
+//! to write through once the node accepts the blob, and a cloud download by a
+//! client with a blob verifier, which needs the whole blob. These paths move blob
+//! bytes exactly as stored: compression and envelopes are not applied or undone.
+
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use bytes::Bytes;
+use futures_util::stream::{self, Stream, StreamExt, TryStreamExt};
+use reqwest::header::CONTENT_LENGTH;
+use reqwest::Body;
+use serde_json::Value;
+use tokio::io::AsyncRead;
+use tokio_util::io::{ReaderStream, StreamReader};
+use tracing::{debug, warn};
+
+use crate::cache::{cache_key, versionhash_accepted};
+use crate::versionhash::VersionHasher;
+use crate::{http_error, JsonRpcResponse, RetrievalStrategy, RpcError, SyscoinClient, SyscoinError, MAX_BLOB_SIZE};
+
+/// Give up looking for the `data` field after this much response prefix
+const MAX_RESPONSE_HEAD: usize = 64 * 1024;
+
+/// Blob bytes arriving incrementally
+pub type BlobByteStream = Pin<Box<dyn Stream<Item = Result<Bytes, SyscoinError>> + Send>>;
+
+impl SyscoinClient {
+    /// Like `create_blob`, but reads the payload from `data` while it is sent.
+    /// `size` must be the exact payload length; it is needed up front for the
+    /// blob size limit and spend accounting.
+    pub async fn create_blob_from_stream<S, E>(&self, data: S, size: usize) -> Result<String, SyscoinError>
+    where
+        S: Stream<Item = Result<Bytes, E>> + Send + 'static,
+        E: Into<SyscoinError> + 'static,
+    {
+        if size > MAX_BLOB_SIZE {
+            return Err(format!("blob size ({}) exceeds maximum allowed ({})", size, MAX_BLOB_SIZE).into());
+        }
+
+        self.run_utxo_preflight().await;
+        let reservation = self.reserve_spend(size).await?;
+        // the upload can't outlast the request timeout, so that is how long it needs the wallet
+        let unlocked = match self.unlock_ahead("syscoincreatenevmblob", self.rpc_client.timeout).await {
+            Ok(guard) => guard,
+            Err(e) => {
+                self.release_spend(reservation);
+                return Err(e);
+            }
+        };
+        // tee the payload for the blob store, which needs it whole
+        let copy = self.blob_store.is_some().then(|| Arc::new(Mutex::new(Vec::with_capacity(size))));
+        let tee = copy.clone();
+        let data = data.inspect(move |chunk| {
+            if let (Some(copy), Ok(bytes)) = (&tee, chunk) {
+                copy.lock().unwrap().extend_from_slice(bytes);
+            }
+        });
+        let result = self.post_streamed_blob(data, size).await;
+        self.relock_after(unlocked, "syscoincreatenevmblob").await;
+
+        let response = match result {
+            Ok(v) => v,
+            Err(e) => {
+                self.release_spend(reservation);
+                return Err(e);
+            }
+        };
+        let hash = response
+            .get("versionhash")
+            .and_then(|v| v.as_str())
+            .ok_or("Missing versionhash")?;
+        if let Some(copy) = copy {
+            let data = std::mem::take(&mut *copy.lock().unwrap());
+            self.store_blob(hash, &data, true).await;
+        }
+        Ok(hash.to_string())
+    }
+
+    /// `create_blob_from_stream` for an `AsyncRead` source of exactly `size` bytes
+    pub async fn create_blob_from_reader<R>(&self, reader: R, size: usize) -> Result<String, SyscoinError>
+    where
+        R: AsyncRead + Send + 'static,
+    {
+        self.create_blob_from_stream(ReaderStream::new(reader), size).await
+    }
+
+    /// Like `get_blob`, but yields the blob bytes as they are received.
+    /// Sources are tried as the client's `RetrievalStrategy` says; with
+    /// `Parallel` the first stream to open wins. The blob cache is not used.
+    pub async fn get_blob_stream(&self, blob_id: &str) -> Result<BlobByteStream, SyscoinError> {
+        match self.retrieval_strategy {
+            RetrievalStrategy::RpcOnly => self.open_rpc_blob_stream(blob_id).await,
+            RetrievalStrategy::CloudOnly => self.open_cloud_blob_stream(blob_id).await,
+            RetrievalStrategy::RpcThenCloud => match self.open_rpc_blob_stream(blob_id).await {
+                Ok(stream) => Ok(stream),
+                Err(e) => {
+                    warn!("streaming from RPC failed ({e}); falling back to cloud");
+                    self.open_cloud_blob_stream(blob_id).await
+                }
+            },
+            RetrievalStrategy::CloudThenRpc => match self.open_cloud_blob_stream(blob_id).await {
+                Ok(stream) => Ok(stream),
+                Err(e) => {
+                    warn!("streaming from cloud failed ({e}); falling back to RPC");
+                    self.open_rpc_blob_stream(blob_id).await
+                }
+            },
+            RetrievalStrategy::Parallel => self.open_first_blob_stream(blob_id).await,
+        }
+    }
+
+    /// `get_blob_stream` as an `AsyncRead`
+    pub async fn get_blob_reader(&self, blob_id: &str) -> Result<impl AsyncRead + Send + Unpin, SyscoinError> {
+        let stream = self.get_blob_stream(blob_id).await?;
+        Ok(StreamReader::new(stream.map_err(io::Error::other)))
+    }
+
+    /// Open the node and cloud streams at once and keep whichever opens first
+    async fn open_first_blob_stream(&self, blob_id: &str) -> Result<BlobByteStream, SyscoinError> {
+        let rpc = self.open_rpc_blob_stream(blob_id);
+        let cloud = self.open_cloud_blob_stream(blob_id);
+        tokio::pin!(rpc, cloud);
+
+        tokio::select! {
+            result = &mut rpc => match result {
+                Ok(stream) => Ok(stream),
+                Err(e) => {
+                    debug!("RPC lost the race for {}: {}", blob_id, e);
+                    cloud.await
+                }
+            },
+            result = &mut cloud => match result {
+                Ok(stream) => Ok(stream),
+                Err(e) => {
+                    debug!("cloud lost the race for {}: {}", blob_id, e);
+                    rpc.await
+                }
+            },
+        }
+    }
+
+    async fn post_streamed_blob<S, E>(&self, data: S, size: usize) -> Result<Value, SyscoinError>
+    where
+        S: Stream<Item = Result<Bytes, E>> + Send + 'static,
+        E: Into<SyscoinError> + 'static,
+    {
+        // same envelope and positional params as `create_blob`, with the hex spliced in
+        let prefix = Bytes::from_static(br#"{"jsonrpc":"2.0","id":1,"method":"syscoincreatenevmblob","params":[""#);
+        let suffix = Bytes::from_static(br#"",false,"blake2s"]}"#);
+        let content_length = prefix.len() + 2 * size + suffix.len();
+
+        let seen = Arc::new(AtomicUsize::new(0));
+        let counted = seen.clone();
+        let hex_chunks = data.map(move |chunk| {
+            let chunk = chunk.map_err(Into::into)?;
+            if counted.fetch_add(chunk.len(), Ordering::Relaxed) + chunk.len() > size {
+                return Err::<Bytes, SyscoinError>(format!("blob stream is longer than the declared {} bytes", size).into());
+            }
+            Ok(Bytes::from(hex::encode(&chunk)))
+        });
+        let tail = stream::once(async move {
+            let total = seen.load(Ordering::Relaxed);
+            if total != size {
+                return Err::<Bytes, SyscoinError>(
+                    format!("blob stream ended after {} of the declared {} bytes", total, size).into(),
+                );
+            }
+            Ok(suffix)
+        });
+        let body = stream::once(async move { Ok::<_, SyscoinError>(prefix) })
+            .chain(hex_chunks)
+            .chain(tail);
+
+        let rpc = &self.rpc_client;
+        let url = format!("{}/wallet/{}", rpc.rpc_url.trim_end_matches('/'), rpc.wallet_name);
+        let resp = rpc
+            .http_client
+            .post(&url)
+            .basic_auth(&rpc.rpc_user, Some(&rpc.rpc_password))
+            .header(CONTENT_LENGTH, content_length)
+            .body(Body::wrap_stream(body))
+            .timeout(rpc.timeout)
+            .send()
+            .await?;
+
+        let status = resp.status();
+        let body = resp.text().await?;
+        debug!("streamed WALLET RPC `syscoincreatenevmblob` → HTTP {}: {}", status, body);
+        if !status.is_success() {
+            return Err(http_error(status, &body));
+        }
+        let jr: JsonRpcResponse<Value> = serde_json::from_str(&body)?;
+        if let Some(err) = jr.error {
+            return Err(Box::new(RpcError::from_value(err)));
+        }
+        jr.result.ok_or_else(|| "missing result in JSON-RPC response".into())
+    }
+
+    /// Start a `getnevmblobdata` download and return once the blob data begins,
+    /// so RPC errors surface here rather than mid-stream
+    async fn open_rpc_blob_stream(&self, blob_id: &str) -> Result<BlobByteStream, SyscoinError> {
+        let actual_blob_id = blob_id.strip_prefix("0x").unwrap_or(blob_id);
+        let request_body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getnevmblobdata",
+            "params": [actual_blob_id, true],
+        });
+
+        let rpc = &self.rpc_client;
+        let resp = rpc
+            .http_client
+            .post(&rpc.rpc_url)
+            .basic_auth(&rpc.rpc_user, Some(&rpc.rpc_password))
+            .json(&request_body)
+            .timeout(rpc.timeout)
+            .send()
+            .await?;
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await?;
+            return Err(http_error(status, &body));
+        }
+
+        let mut body = resp.bytes_stream();
+        let mut scanner = HexDataScanner::default();
+        let mut first = Vec::new();
+        while !scanner.in_data() {
+            match body.next().await {
+                Some(chunk) => first = scanner.feed(&chunk?)?,
+                None => return Err(scanner.missing_data_error()),
+            }
+        }
+
+        let state = (Box::pin(body), scanner, Some(first));
+        let decoded = stream::try_unfold(state, |(mut body, mut scanner, first)| async move {
+            if let Some(first) = first.filter(|f| !f.is_empty()) {
+                return Ok(Some((Bytes::from(first), (body, scanner, None))));
+            }
+            loop {
+                if scanner.finished() {
+                    return Ok(None);
+                }
+                let Some(chunk) = body.next().await else {
+                    return Err::<_, SyscoinError>("blob data stream ended early".into());
+                };
+                let decoded = scanner.feed(&chunk?)?;
+                if !decoded.is_empty() {
+                    return Ok(Some((Bytes::from(decoded), (body, scanner, None))));
+                }
+            }
+        });
+        Ok(Box::pin(decoded))
+    }
+
+    /// Open the first PoDA mirror that answers. Its bytes are checked as they pass,
+    /// so the stream ends in an error when they turn out not to be `blob_id`.
+    async fn open_cloud_blob_stream(&self, blob_id: &str) -> Result<BlobByteStream, SyscoinError> {
+        let mut last_error: Option<SyscoinError> = None;
+        for base in self.poda_urls() {
+            let url = format!("{}/blob/{}", base, blob_id);
+            match self.rpc_client.http_client.get(&url).send().await {
+                Ok(resp) if resp.status().is_success() => {
+                    return Ok(self.verify_cloud_stream(blob_id, Box::pin(resp.bytes_stream().map_err(SyscoinError::from))));
+                }
+                Ok(resp) => last_error = Some(format!("HTTP error: {} fetching {}", resp.status(), url).into()),
+                Err(e) => last_error = Some(e.into()),
+            }
+            debug!("PoDA mirror {} could not stream {}", base, blob_id);
+        }
+        Err(last_error.unwrap_or_else(|| "no PoDA mirrors configured".into()))
+    }
+
+    /// Apply `accepts_blob` to a cloud stream: hash the bytes as they pass and end
+    /// with an error if they are rejected. A verifier needs the whole blob, so
+    /// with one set a copy is kept until the end.
+    fn verify_cloud_stream(&self, blob_id: &str, body: BlobByteStream) -> BlobByteStream {
+        let key = cache_key(blob_id);
+        let check = self.check_versionhash;
+        let verifier = self.blob_verifier.clone();
+        let copy = verifier.as_ref().map(|_| Vec::new());
+        let state = (body, VersionHasher::default(), copy);
+        let verified = stream::try_unfold(state, move |(mut body, mut hasher, mut copy)| {
+            let (key, verifier) = (key.clone(), verifier.clone());
+            async move {
+                if let Some(chunk) = body.next().await {
+                    let chunk = chunk?;
+                    hasher.update(&chunk);
+                    if let Some(copy) = &mut copy {
+                        copy.extend_from_slice(&chunk);
+                    }
+                    return Ok(Some((chunk, (body, hasher, copy))));
+                }
+                let accepted = versionhash_accepted(check, &key, &hasher.finish())
+                    && verifier.zip(copy).is_none_or(|(verify, data)| verify(&key, &data));
+                if !accepted {
+                    return Err::<_, SyscoinError>(format!("cloud copy of blob {} failed verification", key).into());
+                }
+                Ok(None)
+            }
+        });
+        Box::pin(verified)
+    }
+}
+
+/// Incrementally extracts and hex-decodes the `"data"` string of a JSON-RPC response
+#[derive(Default)]
+struct HexDataScanner {
+    /// Response bytes seen before the data string starts
+    head: Vec<u8>,
+    state: ScanState,
+    /// Hex digits of the data string consumed so far
+    digits: usize,
+    high_nibble: Option<u8>,
+}
+
+#[derive(Default, PartialEq, Eq)]
+enum ScanState {
+    #[default]
+    Head,
+    Data,
+    Done,
+}
+
+impl HexDataScanner {
+    fn in_data(&self) -> bool {
+        self.state != ScanState::Head
+    }
+
+    fn finished(&self) -> bool {
+        self.state == ScanState::Done
+    }
+
+    /// Consume a response chunk and return the blob bytes it completes
+    fn feed(&mut self, chunk: &[u8]) -> Result<Vec<u8>, SyscoinError> {
+        let data = match self.state {
+            ScanState::Head => {
+                self.head.extend_from_slice(chunk);
+                let Some(start) = find_data_string(&self.head) else {
+                    if self.head.len() > MAX_RESPONSE_HEAD {
+                        return Err("no data field in getnevmblobdata response".into());
+                    }
+                    return Ok(Vec::new());
+                };
+                self.state = ScanState::Data;
+                let rest = self.head.split_off(start);
+                return self.decode(&rest);
+            }
+            ScanState::Data => chunk,
+            ScanState::Done => return Ok(Vec::new()),
+        };
+        self.decode(data)
+    }
+
+    fn decode(&mut self, data: &[u8]) -> Result<Vec<u8>, SyscoinError> {
+        let mut out = Vec::with_capacity(data.len() / 2);
+        for &c in data {
+            if c == b'"' {
+                if self.high_nibble.is_some() {
+                    return Err("blob data has an odd number of hex digits".into());
+                }
+                self.state = ScanState::Done;
+                break;
+            }
+            self.digits += 1;
+            // optional 0x prefix; its '0' has already been taken as a high nibble
+            if self.digits == 2 && matches!(c, b'x' | b'X') && self.high_nibble == Some(0) {
+                self.high_nibble = None;
+                continue;
+            }
+            let nibble = hex_value(c).ok_or_else(|| format!("invalid hex digit {:?} in blob data", c as char))?;
+            match self.high_nibble.take() {
+                Some(high) => out.push(high << 4 | nibble),
+                None => self.high_nibble = Some(nibble),
+            }
+        }
+        Ok(out)
+    }
+
+    fn missing_data_error(&self) -> SyscoinError {
+        match serde_json::from_slice::<JsonRpcResponse<Value>>(&self.head) {
+            Ok(JsonRpcResponse { error: Some(err), .. }) => Box::new(RpcError::from_value(err)),
+            _ => "Missing data in getnevmblobdata response".into(),
+        }
+    }
+}
+
+/// Offset just past the opening quote of the `"data"` value, once it is in `head`
+fn find_data_string(head: &[u8]) -> Option<usize> {
+    const KEY: &[u8] = b"\"data\"";
+    let key_at = head.windows(KEY.len()).position(|w| w == KEY)?;
+    let mut i = key_at + KEY.len();
+    let mut seen_colon = false;
+    while let Some(&c) = head.get(i) {
+        match c {
+            b' ' | b'\t' | b'\r' | b'\n' => {}
+            b':' if !seen_colon => seen_colon = true,
+            b'"' if seen_colon => return Some(i + 1),
+            _ => return None,
+        }
+        i += 1;
+    }
+    None
+}
+
+fn hex_value(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
@@ -0,0 +1,172 @@
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::debug;
+
+use crate::{Amount, RpcClient, SyscoinClient, SyscoinError};
+
+/// Input of a blob carrier transaction
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TxInput {
+    /// Spent outpoint; absent for coinbase inputs
+    #[serde(default)]
+    pub txid: Option<String>,
+    #[serde(default)]
+    pub vout: Option<u32>,
+    pub sequence: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct ScriptPubKey {
+    #[serde(default)]
+    asm: String,
+    hex: String,
+    #[serde(rename = "type")]
+    script_type: String,
+    #[serde(default)]
+    address: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct RawOutput {
+    value: f64,
+    n: u32,
+    #[serde(rename = "scriptPubKey")]
+    script_pub_key: ScriptPubKey,
+}
+
+/// Output of a blob carrier transaction
+#[derive(Debug, Clone, PartialEq)]
+pub struct TxOutput {
+    pub index: u32,
+    pub value: Amount,
+    /// Script type as reported by the node, e.g. `witness_v0_keyhash` or `nulldata`
+    pub script_type: String,
+    pub script_hex: String,
+    pub address: Option<String>,
+}
+
+/// The `OP_RETURN` output committing the transaction to the blob
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlobCommitment {
+    pub output_index: u32,
+    /// Hex of the data pushed after `OP_RETURN`
+    pub data: String,
+}
+
+/// A blob carrier transaction as it landed on chain
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlobTransaction {
+    pub txid: String,
+    pub version: u32,
+    pub inputs: Vec<TxInput>,
+    pub outputs: Vec<TxOutput>,
+    /// Known when the node reports it or the transaction belongs to the configured wallet
+    pub fee: Option<Amount>,
+    pub vsize: u64,
+    /// `None` while the transaction is still in the mempool
+    pub block_hash: Option<String>,
+    pub confirmations: u64,
+    pub commitment: Option<BlobCommitment>,
+}
+
+impl SyscoinClient {
+    /// Resolve the carrier transaction of `blob_id` and decode it
+    pub async fn get_blob_transaction(&self, blob_id: &str) -> Result<BlobTransaction, SyscoinError> {
+        let info = self
+            .lookup_blob(blob_id)
+            .await?
+            .ok_or_else(|| format!("blob {} is not known to the node", blob_id))?;
+        let txid = info
+            .get("txid")
+            .and_then(|v| v.as_str())
+            .ok_or("Missing txid in getnevmblobdata response")?;
+        let versionhash = info
+            .get("versionhash")
+            .and_then(|v| v.as_str())
+            .unwrap_or(blob_id.strip_prefix("0x").unwrap_or(blob_id));
+
+        // Without -txindex the node can only find confirmed transactions given their block
+        let mut params = vec![json!(txid), json!(true)];
+        if let Some(block_hash) = info.get("blockhash").and_then(|v| v.as_str()) {
+            params.push(json!(block_hash));
+        }
+        let raw = self.rpc_client.call("getrawtransaction", &params).await?;
+
+        let fee = match raw.get("fee").and_then(Value::as_f64) {
+            Some(fee) => Amount::from_sys(fee.abs()).ok(),
+            None => self.wallet_tx_fee(txid).await,
+        };
+        decode_blob_transaction(&raw, versionhash, fee)
+    }
+
+    /// Fee of a wallet transaction, if the configured wallet knows it
+    async fn wallet_tx_fee(&self, txid: &str) -> Option<Amount> {
+        match self.rpc_client.call_wallet("gettransaction", &[json!(txid)]).await {
+            Ok(tx) => tx.get("fee").and_then(Value::as_f64).and_then(|f| Amount::from_sys(f.abs()).ok()),
+            Err(e) => {
+                debug!("gettransaction {} unavailable: {}", txid, e);
+                None
+            }
+        }
+    }
+}
+
+fn decode_blob_transaction(raw: &Value, versionhash: &str, fee: Option<Amount>) -> Result<BlobTransaction, SyscoinError> {
+    let txid = raw
+        .get("txid")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing txid in getrawtransaction response")?;
+    let inputs: Vec<TxInput> = serde_json::from_value(raw.get("vin").cloned().unwrap_or(json!([])))?;
+    let raw_outputs: Vec<RawOutput> = serde_json::from_value(raw.get("vout").cloned().unwrap_or(json!([])))?;
+
+    let commitment = find_commitment(&raw_outputs, versionhash);
+    let outputs = raw_outputs
+        .into_iter()
+        .map(|o| {
+            Ok(TxOutput {
+                index: o.n,
+                value: Amount::from_sys(o.value)?,
+                script_type: o.script_pub_key.script_type,
+                script_hex: o.script_pub_key.hex,
+                address: o.script_pub_key.address,
+            })
+        })
+        .collect::<Result<Vec<_>, SyscoinError>>()?;
+
+    Ok(BlobTransaction {
+        txid: txid.to_owned(),
+        version: raw.get("version").and_then(Value::as_u64).unwrap_or_default() as u32,
+        inputs,
+        outputs,
+        fee,
+        vsize: raw.get("vsize").and_then(Value::as_u64).unwrap_or_default(),
+        block_hash: raw.get("blockhash").and_then(|v| v.as_str()).map(|s| s.to_owned()),
+        confirmations: raw.get("confirmations").and_then(Value::as_u64).unwrap_or_default(),
+        commitment,
+    })
+}
+
+/// Pick the `OP_RETURN` output carrying `versionhash`, falling back to the first one
+fn find_commitment(outputs: &[RawOutput], versionhash: &str) -> Option<BlobCommitment> {
+    let versionhash = versionhash.to_ascii_lowercase();
+    let commitments: Vec<BlobCommitment> = outputs
+        .iter()
+        .filter(|o| o.script_pub_key.script_type == "nulldata")
+        .map(|o| BlobCommitment {
+            output_index: o.n,
+            data: o
+                .script_pub_key
+                .asm
+                .split_whitespace()
+                .skip(1)
+                .collect::<Vec<_>>()
+                .concat(),
+        })
+        .collect();
+
+    commitments
+        .iter()
+        .find(|c| !versionhash.is_empty() && c.data.to_ascii_lowercase().contains(&versionhash))
+        .or_else(|| commitments.first())
+        .cloned()
+}
@@ -0,0 +1,224 @@
+use serde::Deserialize;
+use serde_json::json;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::{Amount, RpcClient, SyscoinClient, SyscoinError, SATOSHIS_PER_SYS};
+
+/// Default mempool limit on unconfirmed ancestors of a transaction
+const DEFAULT_MAX_ANCESTORS: u64 = 25;
+
+/// An unspent output of the configured wallet, as reported by `listunspent`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Utxo {
+    pub txid: String,
+    pub vout: u32,
+    #[serde(default)]
+    pub address: Option<String>,
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Value in SYS
+    pub amount: f64,
+    pub confirmations: u64,
+    /// Number of in-mempool ancestors, including this output's own transaction (unconfirmed only)
+    #[serde(rename = "ancestorcount", default)]
+    pub ancestor_count: Option<u64>,
+    pub spendable: bool,
+    #[serde(default)]
+    pub solvable: bool,
+    /// Whether the wallet considers this output safe to spend
+    #[serde(default = "default_safe")]
+    pub safe: bool,
+}
+
+fn default_safe() -> bool {
+    true
+}
+
+impl Utxo {
+    /// Whether a new transaction can spend this output without hitting the ancestor limit
+    pub fn is_usable(&self, max_ancestors: u64) -> bool {
+        self.spendable
+            && self.safe
+            && (self.confirmations > 0 || self.ancestor_count.unwrap_or(1) < max_ancestors)
+    }
+
+    /// Value of the output in whole satoshis
+    pub fn value(&self) -> Amount {
+        Amount::from_sat((self.amount.max(0.0) * SATOSHIS_PER_SYS).round() as u64)
+    }
+}
+
+/// Outcome of a consolidation or split transaction
+#[derive(Debug, Clone, PartialEq)]
+pub struct UtxoTransaction {
+    pub txid: String,
+    /// Number of wallet outputs spent
+    pub inputs: usize,
+    /// Number of outputs created (excluding change)
+    pub outputs: usize,
+}
+
+/// What the `create_blob` pre-flight does when usable UTXOs run low
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreflightAction {
+    /// Log a warning and submit anyway
+    Warn,
+    /// Split the largest usable UTXO into `outputs` equal outputs before submitting
+    Split { outputs: usize },
+}
+
+/// Pre-flight check run before each blob submission so parallel submissions
+/// have distinct inputs and don't chain into the mempool ancestor limit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UtxoPreflight {
+    /// Minimum number of usable UTXOs expected before a submission
+    pub min_usable: usize,
+    /// Unconfirmed outputs with this many ancestors or more are not counted as usable
+    pub max_ancestors: u64,
+    pub action: PreflightAction,
+    /// Fee rate in sat/vB for split transactions; `None` lets the wallet estimate
+    pub fee_rate: Option<f64>,
+}
+
+impl Default for UtxoPreflight {
+    fn default() -> Self {
+        Self {
+            min_usable: 1,
+            max_ancestors: DEFAULT_MAX_ANCESTORS,
+            action: PreflightAction::Warn,
+            fee_rate: None,
+        }
+    }
+}
+
+/// Pre-flight configuration plus a lock so concurrent submissions trigger at most one split
+pub(crate) struct PreflightGuard {
+    config: UtxoPreflight,
+    lock: Mutex<()>,
+}
+
+impl SyscoinClient {
+    /// Run the given pre-flight check before every `create_blob` submission
+    pub fn with_utxo_preflight(mut self, preflight: UtxoPreflight) -> Self {
+        self.utxo_preflight = Some(PreflightGuard {
+            config: preflight,
+            lock: Mutex::new(()),
+        });
+        self
+    }
+
+    /// List unspent outputs of the configured wallet with at least `min_conf` confirmations
+    pub async fn list_unspent(&self, min_conf: u32) -> Result<Vec<Utxo>, SyscoinError> {
+        let resp = self
+            .rpc_client
+            .call_wallet("listunspent", &[json!(min_conf)])
+            .await?;
+        Ok(serde_json::from_value(resp)?)
+    }
+
+    /// Merge the smallest confirmed UTXOs so that at most `target_count` remain.
+    /// `fee_rate` is in sat/vB; `None` lets the wallet estimate it.
+    /// Returns `None` when the wallet already has `target_count` UTXOs or fewer.
+    pub async fn consolidate_utxos(
+        &self,
+        target_count: usize,
+        fee_rate: Option<f64>,
+    ) -> Result<Option<UtxoTransaction>, SyscoinError> {
+        let mut utxos: Vec<Utxo> = self
+            .list_unspent(1)
+            .await?
+            .into_iter()
+            .filter(|u| u.spendable && u.safe)
+            .collect();
+        let target_count = target_count.max(1);
+        if utxos.len() <= target_count {
+            return Ok(None);
+        }
+
+        utxos.sort_by_key(Utxo::value);
+        let merge = &utxos[..utxos.len() - target_count + 1];
+        let total: Amount = merge.iter().map(Utxo::value).sum();
+        info!("consolidating {} UTXOs ({})", merge.len(), total);
+
+        let address = self.change_address().await?;
+        let txid = self
+            .send_outputs(Some(merge), &[(address, total)], true, fee_rate)
+            .await?;
+        Ok(Some(UtxoTransaction { txid, inputs: merge.len(), outputs: 1 }))
+    }
+
+    /// Split the largest usable UTXO into `outputs` equal outputs back to the wallet.
+    /// Usable means under the pre-flight's `max_ancestors` when one is configured.
+    pub async fn split_utxos(
+        &self,
+        outputs: usize,
+        fee_rate: Option<f64>,
+    ) -> Result<UtxoTransaction, SyscoinError> {
+        let outputs = outputs.max(2);
+        let max_ancestors = self.utxo_preflight.as_ref().map_or(DEFAULT_MAX_ANCESTORS, |g| g.config.max_ancestors);
+        let largest = self
+            .list_unspent(0)
+            .await?
+            .into_iter()
+            .filter(|u| u.is_usable(max_ancestors))
+            .max_by_key(Utxo::value)
+            .ok_or("no usable UTXO to split")?;
+
+        let each = Amount::from_sat(largest.value().to_sat() / outputs as u64);
+        let mut targets = Vec::with_capacity(outputs);
+        for _ in 0..outputs {
+            targets.push((self.change_address().await?, each));
+        }
+        info!("splitting {}:{} into {} outputs of {}", largest.txid, largest.vout, outputs, each);
+
+        // fee comes out of the first output; the rounding remainder goes to change
+        let txid = self
+            .send_outputs(Some(std::slice::from_ref(&largest)), &targets, true, fee_rate)
+            .await?;
+        Ok(UtxoTransaction { txid, inputs: 1, outputs })
+    }
+
+    /// Apply the configured pre-flight before a submission.
+    /// Never fails the submission; problems are logged and the blob is sent anyway.
+    pub(crate) async fn run_utxo_preflight(&self) {
+        let Some(guard) = &self.utxo_preflight else {
+            return;
+        };
+        let config = guard.config;
+        let _lock = guard.lock.lock().await;
+
+        let usable = match self.list_unspent(0).await {
+            Ok(utxos) => utxos.iter().filter(|u| u.is_usable(config.max_ancestors)).count(),
+            Err(e) => {
+                warn!("UTXO pre-flight skipped, listunspent failed: {}", e);
+                return;
+            }
+        };
+        if usable >= config.min_usable {
+            return;
+        }
+
+        match config.action {
+            PreflightAction::Warn => {
+                warn!(
+                    "only {} usable UTXOs (want {}); parallel blob submissions may collide",
+                    usable, config.min_usable
+                );
+            }
+            PreflightAction::Split { outputs } => {
+                warn!("only {} usable UTXOs (want {}); splitting into {}", usable, config.min_usable, outputs);
+                if let Err(e) = self.split_utxos(outputs, config.fee_rate).await {
+                    warn!("UTXO split failed: {}", e);
+                }
+            }
+        }
+    }
+
+    async fn change_address(&self) -> Result<String, SyscoinError> {
+        let resp = self.rpc_client.call_wallet("getrawchangeaddress", &[]).await?;
+        resp.as_str()
+            .map(|s| s.to_owned())
+            .ok_or_else(|| "getrawchangeaddress returned non-string".into())
+    }
+}
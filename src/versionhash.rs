@@ -0,0 +1,28 @@
+//! Local computation of blob version hashes.
+//!
+//! `create_blob` asks the node for `blake2s` version hashes, taken here to be
+//! the BLAKE2s-256 digest of the stored bytes. Computing it here lets the client
+//! look a blob up before paying to publish it again. The formula is not yet
+//! pinned against a versionhash issued by a node, so cloud reads only enforce
+//! it when `with_versionhash_check` is turned on.
+
+use blake2::{Blake2s256, Digest};
+
+/// Version hash (lowercase hex, no `0x`) the node assigns to a blob holding exactly `data`
+pub fn blob_versionhash(data: &[u8]) -> String {
+    hex::encode(Blake2s256::digest(data))
+}
+
+/// `blob_versionhash` over data that arrives in pieces
+#[derive(Default)]
+pub(crate) struct VersionHasher(Blake2s256);
+
+impl VersionHasher {
+    pub(crate) fn update(&mut self, chunk: &[u8]) {
+        self.0.update(chunk);
+    }
+
+    pub(crate) fn finish(self) -> String {
+        hex::encode(self.0.finalize())
+    }
+}
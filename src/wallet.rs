@@ -0,0 +1,281 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use secrecy::{ExposeSecret, SecretString};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::sync::{Mutex, MutexGuard};
+use tracing::{info, warn};
+
+use crate::{rpc_error_code, RpcClient, SyscoinClient, SyscoinError, RPC_WALLET_UNLOCK_NEEDED};
+
+const DEFAULT_UNLOCK_WINDOW: Duration = Duration::from_secs(30);
+
+/// Passphrase kept by the client to unlock an encrypted wallet on demand
+pub(crate) struct AutoUnlock {
+    passphrase: SecretString,
+    window: Duration,
+    // Serialises unlock → submit → relock so one call's relock can't land
+    // in the middle of another call's submission.
+    lock: Mutex<()>,
+}
+
+/// Held for a call prepared with `unlock_ahead`: keeps other calls from relocking
+/// the wallet meanwhile and remembers the unlock state to restore afterwards
+pub(crate) struct UnlockScope<'a> {
+    _guard: MutexGuard<'a, ()>,
+    /// `unlocked_until` before the call, when `unlock_ahead` changed it
+    restore_until: Option<u64>,
+}
+
+/// Settings used when `create_or_load_wallet_with_options` has to create the wallet.
+/// Options left as `None` fall back to the node's defaults.
+#[derive(Debug, Clone, Default)]
+pub struct CreateWalletOptions {
+    /// Create a descriptor wallet
+    pub descriptors: Option<bool>,
+    /// Watch-only wallet without private keys (e.g. for monitors)
+    pub disable_private_keys: bool,
+    /// Start without keys or HD seed
+    pub blank: bool,
+    /// Encrypt the new wallet with this passphrase
+    pub passphrase: Option<SecretString>,
+    /// Track reused addresses and avoid spending from them
+    pub avoid_reuse: bool,
+    /// Add the wallet to (or remove it from) the node's startup list; also applied on load
+    pub load_on_startup: Option<bool>,
+}
+
+impl CreateWalletOptions {
+    /// Positional `createwallet` arguments
+    pub(crate) fn to_params(&self, wallet_name: &str) -> Vec<Value> {
+        vec![
+            json!(wallet_name),
+            json!(self.disable_private_keys),
+            json!(self.blank),
+            json!(self.passphrase.as_ref().map(|p| p.expose_secret()).unwrap_or("")),
+            json!(self.avoid_reuse),
+            json!(self.descriptors),
+            json!(self.load_on_startup),
+        ]
+    }
+}
+
+/// One bucket of wallet funds as reported by `getbalances`, in SYS
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+pub struct BalanceBreakdown {
+    /// Confirmed funds plus our own unconfirmed change; spendable for blob fees
+    pub trusted: f64,
+    /// Unconfirmed funds received from others
+    pub untrusted_pending: f64,
+    /// Coinbase outputs that have not matured yet
+    pub immature: f64,
+    /// Funds on reused addresses (only reported with `avoid_reuse` wallets)
+    #[serde(default)]
+    pub used: Option<f64>,
+}
+
+/// Typed result of `getbalances`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+pub struct Balances {
+    pub mine: BalanceBreakdown,
+    /// Present only when the wallet tracks watch-only addresses
+    #[serde(default)]
+    pub watchonly: Option<BalanceBreakdown>,
+}
+
+/// Rescan state reported by `getwalletinfo`
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum WalletScan {
+    /// `false` while no rescan is running
+    Idle(bool),
+    /// Elapsed seconds and fractional progress of a running rescan
+    Running { duration: u64, progress: f64 },
+}
+
+impl Default for WalletScan {
+    fn default() -> Self {
+        WalletScan::Idle(false)
+    }
+}
+
+/// Typed result of `getwalletinfo`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct WalletInfo {
+    #[serde(rename = "walletname")]
+    pub wallet_name: String,
+    #[serde(rename = "walletversion")]
+    pub wallet_version: u64,
+    /// Database format, e.g. `sqlite` or `bdb`
+    #[serde(default)]
+    pub format: Option<String>,
+    #[serde(rename = "txcount")]
+    pub tx_count: u64,
+    #[serde(rename = "keypoolsize", default)]
+    pub keypool_size: u64,
+    /// Unix time until which an encrypted wallet stays unlocked (0 = locked).
+    /// Absent for unencrypted wallets.
+    #[serde(default)]
+    pub unlocked_until: Option<u64>,
+    /// Fee rate override in SYS/kvB set with `settxfee`
+    #[serde(rename = "paytxfee", default)]
+    pub pay_tx_fee: f64,
+    pub private_keys_enabled: bool,
+    #[serde(default)]
+    pub avoid_reuse: bool,
+    #[serde(default)]
+    pub descriptors: bool,
+    #[serde(default)]
+    pub scanning: WalletScan,
+}
+
+impl WalletInfo {
+    /// Whether the wallet is encrypted (locked or not)
+    pub fn is_encrypted(&self) -> bool {
+        self.unlocked_until.is_some()
+    }
+
+    /// Whether the wallet can sign for blob fees without a watch-only setup
+    pub fn can_sign(&self) -> bool {
+        self.private_keys_enabled
+    }
+}
+
+impl SyscoinClient {
+    /// Fetch the typed `getwalletinfo` of the configured wallet
+    pub async fn get_wallet_info(&self) -> Result<WalletInfo, SyscoinError> {
+        let resp = self.rpc_client.call_wallet("getwalletinfo", &[]).await?;
+        Ok(serde_json::from_value(resp)?)
+    }
+
+    /// Names of the wallets currently loaded on the node
+    pub async fn list_wallets(&self) -> Result<Vec<String>, SyscoinError> {
+        let resp = self.rpc_client.call("listwallets", &[]).await?;
+        Ok(serde_json::from_value(resp)?)
+    }
+
+    /// Encrypt the configured wallet with `passphrase`.
+    /// The node locks the wallet afterwards; older nodes also shut down and need a restart.
+    pub async fn encrypt_wallet(&self, passphrase: &SecretString) -> Result<(), SyscoinError> {
+        info!("encrypting wallet {}", self.rpc_client.wallet_name());
+        self.rpc_client
+            .call_wallet("encryptwallet", &[json!(passphrase.expose_secret())])
+            .await?;
+        Ok(())
+    }
+
+    /// Unlock the configured wallet for `duration` (whole seconds, at least one)
+    pub async fn unlock_wallet(&self, passphrase: &SecretString, duration: Duration) -> Result<(), SyscoinError> {
+        let secs = duration.as_secs().max(1);
+        info!("unlocking wallet {} for {}s", self.rpc_client.wallet_name(), secs);
+        self.rpc_client
+            .call_wallet("walletpassphrase", &[json!(passphrase.expose_secret()), json!(secs)])
+            .await?;
+        Ok(())
+    }
+
+    /// Lock the configured wallet immediately
+    pub async fn lock_wallet(&self) -> Result<(), SyscoinError> {
+        info!("locking wallet {}", self.rpc_client.wallet_name());
+        self.rpc_client.call_wallet("walletlock", &[]).await?;
+        Ok(())
+    }
+
+    /// Let `create_blob` unlock an encrypted wallet when the node asks for it
+    /// (RPC -13), keeping it unlocked for at most `unlock_window` and relocking
+    /// right after the submission. `None` uses a 30 second window.
+    pub fn with_wallet_passphrase(mut self, passphrase: SecretString, unlock_window: Option<Duration>) -> Self {
+        self.auto_unlock = Some(AutoUnlock {
+            passphrase,
+            window: unlock_window.unwrap_or(DEFAULT_UNLOCK_WINDOW),
+            lock: Mutex::new(()),
+        });
+        self
+    }
+
+    /// Wallet RPC call that retries once under a scoped unlock when the wallet
+    /// is locked and a passphrase was configured with `with_wallet_passphrase`.
+    pub(crate) async fn call_wallet_unlocked(&self, method: &str, params: &[Value]) -> Result<Value, SyscoinError> {
+        let err = match self.rpc_client.call_wallet(method, params).await {
+            Ok(v) => return Ok(v),
+            Err(e) => e,
+        };
+        let Some(auto) = &self.auto_unlock else {
+            return Err(err);
+        };
+        if rpc_error_code(&err) != Some(RPC_WALLET_UNLOCK_NEEDED) {
+            return Err(err);
+        }
+
+        let _guard = auto.lock.lock().await;
+        info!("wallet locked; unlocking for `{}`", method);
+        self.unlock_wallet(&auto.passphrase, auto.window).await?;
+        let result = self.rpc_client.call_wallet(method, params).await;
+        if let Err(e) = self.lock_wallet().await {
+            warn!("failed to relock wallet after `{}`: {}", method, e);
+        }
+        result
+    }
+
+    /// Unlock ahead of a call that cannot be replayed after a -13 (e.g. a streamed upload).
+    /// When auto-unlock is configured the returned scope holds the unlock lock, so no
+    /// other call relocks the wallet meanwhile, and an encrypted wallet is unlocked
+    /// until at least `expected` (or the unlock window, if longer) from now.
+    /// Hand the scope to `relock_after` once the call is done.
+    pub(crate) async fn unlock_ahead(&self, method: &str, expected: Duration) -> Result<Option<UnlockScope<'_>>, SyscoinError> {
+        let Some(auto) = &self.auto_unlock else {
+            return Ok(None);
+        };
+        let guard = auto.lock.lock().await;
+        let mut scope = UnlockScope { _guard: guard, restore_until: None };
+        // `None` means the wallet is not encrypted
+        let Some(until) = self.get_wallet_info().await?.unlocked_until else {
+            return Ok(Some(scope));
+        };
+        let needed = auto.window.max(expected);
+        if until > unix_now() + needed.as_secs() {
+            return Ok(Some(scope));
+        }
+        info!("unlocking wallet ahead of `{}`", method);
+        self.unlock_wallet(&auto.passphrase, needed).await?;
+        scope.restore_until = Some(until);
+        Ok(Some(scope))
+    }
+
+    /// Put the wallet back the way `unlock_ahead` found it: locked, or unlocked
+    /// for whatever was left of an earlier unlock
+    pub(crate) async fn relock_after(&self, scope: Option<UnlockScope<'_>>, method: &str) {
+        let (Some(auto), Some(until)) = (&self.auto_unlock, scope.as_ref().and_then(|s| s.restore_until)) else {
+            return;
+        };
+        let remaining = until.saturating_sub(unix_now());
+        let result = if remaining > 0 {
+            self.unlock_wallet(&auto.passphrase, Duration::from_secs(remaining)).await
+        } else {
+            self.lock_wallet().await
+        };
+        if let Err(e) = result {
+            warn!("failed to relock wallet after `{}`: {}", method, e);
+        }
+    }
+
+    /// Unload a wallet from the node; it stays on disk and can be loaded again
+    pub async fn unload_wallet(&self, wallet_name: &str) -> Result<(), SyscoinError> {
+        info!("unloading wallet {}", wallet_name);
+        let resp = self.rpc_client.call("unloadwallet", &[json!(wallet_name)]).await?;
+        if let Some(warning) = resp.get("warning").and_then(|v| v.as_str()).filter(|w| !w.is_empty()) {
+            warn!("unloadwallet {}: {}", wallet_name, warning);
+        }
+        Ok(())
+    }
+
+    /// Fetch confirmed, pending and immature balances of the configured wallet
+    pub async fn get_balances(&self) -> Result<Balances, SyscoinError> {
+        let resp = self.rpc_client.call_wallet("getbalances", &[]).await?;
+        Ok(serde_json::from_value(resp)?)
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
@@ -2,9 +2,12 @@
 mod tests {
     use mockito::Server;
     use serde_json::json;
-    use tokio;
-    use bitcoin_da_client::{BitcoinDaFinalityMode, SyscoinClient};
-    use hex;
+    use std::time::Duration;
+    use bitcoin_da_client::{
+        AddressPurpose, AddressType, Amount, BalanceEvent, BalanceMonitor, BalanceMonitorConfig, BitcoinDaFinalityMode, BlobBatcher, BudgetExceeded,
+        BudgetLimit, Codec, CompressionConfig, CreateWalletOptions, EnvelopeOptions, ErasureConfig, FeeBumpPolicy, FinalityWaitOptions, MirrorStrategy, PreflightAction, RetrievalStrategy, SecretString, SendOptions, SpendPolicy, SyscoinClient,
+        UtxoPreflight, WalletScan, rpc_error_code,
+    };
 
 
     #[tokio::test]
@@ -398,7 +401,7 @@ mod tests {
         mock_server
             .mock("POST", "/")
             .match_body(mockito::Matcher::JsonString(
-                r#"{"jsonrpc":"2.0","id":1,"method":"getnevmblobdata","params":["deadbeef"]}"#.to_string()
+                r#"{"jsonrpc":"2.0","id":1,"method":"getnevmblobdata","params":["deadbeef",false]}"#.to_string()
             ))
             .with_status(200)
             .with_header("content-type", "application/json")
@@ -713,5 +716,2390 @@ mod tests {
         assert_eq!(fee, 2);
     }
 
-}
+    fn mock_blob_base_fee(mock_server: &mut mockito::ServerGuard, mempool_min_fee: f64) {
+        mock_server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::Regex("estimatesmartfee".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"result": {"feerate": 0.000001}, "error": null, "id": 1}).to_string())
+            .create();
+        mock_server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::Regex("getmempoolinfo".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "result": { "mempoolminfee": mempool_min_fee, "minrelaytxfee": 0.000015 },
+                    "error": null,
+                    "id": 1
+                })
+                .to_string(),
+            )
+            .create();
+    }
+
+    #[tokio::test]
+    async fn test_spend_policy_rejects_high_fee_per_byte() {
+        let mut mock_server = std::thread::spawn(|| Server::new())
+            .join()
+            .expect("Failed to create mock server");
+
+        // 0.002 SYS/kvb -> 2 sat per blob byte
+        mock_blob_base_fee(&mut mock_server, 0.002);
+        let submit = mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .expect(0)
+            .create();
+
+        let client = SyscoinClient::new(
+            &mock_server.url(),
+            "user",
+            "password",
+            "http://poda.example.com",
+            None,
+            "test_wallet",
+        )
+        .unwrap()
+        .with_spend_policy(SpendPolicy {
+            max_fee_per_byte: Some(1),
+            ..Default::default()
+        });
+
+        let err = client.create_blob(&[1, 2, 3, 4]).await.unwrap_err();
+        let exceeded = err
+            .downcast_ref::<BudgetExceeded>()
+            .expect("Expected a BudgetExceeded error");
+        assert_eq!(exceeded.limit, BudgetLimit::FeePerByte);
+        assert_eq!(exceeded.requested, 2);
+        submit.assert();
+    }
+
+    #[tokio::test]
+    async fn test_spend_policy_enforces_rolling_budget() {
+        let mut mock_server = std::thread::spawn(|| Server::new())
+            .join()
+            .expect("Failed to create mock server");
+
+        mock_blob_base_fee(&mut mock_server, 0.002);
+        mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"result": {"versionhash": "deadbeef"}, "error": null, "id": 1}).to_string())
+            .expect(1)
+            .create();
+
+        let client = SyscoinClient::new(
+            &mock_server.url(),
+            "user",
+            "password",
+            "http://poda.example.com",
+            None,
+            "test_wallet",
+        )
+        .unwrap()
+        .with_spend_policy(SpendPolicy {
+            hourly_budget: Some(10),
+            ..Default::default()
+        });
+
+        // 4 bytes * 2 sat = 8 sat fits the hourly budget once, not twice
+        assert_eq!(client.create_blob(&[1, 2, 3, 4]).await.unwrap(), "deadbeef");
+        let err = client.create_blob(&[5, 6, 7, 8]).await.unwrap_err();
+        let exceeded = err.downcast_ref::<BudgetExceeded>().unwrap();
+        assert_eq!(exceeded.limit, BudgetLimit::Hourly);
+        assert_eq!(exceeded.requested, 16);
+        assert_eq!(client.spend_usage().unwrap().last_hour, 8);
+    }
+
+    #[tokio::test]
+    async fn test_get_balances() {
+        let mut mock_server = std::thread::spawn(|| Server::new())
+            .join()
+            .expect("Failed to create mock server");
+
+        mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(mockito::Matcher::Regex("getbalances".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "result": {
+                        "mine": { "trusted": 1.5, "untrusted_pending": 0.25, "immature": 0.0 },
+                        "watchonly": { "trusted": 2.0, "untrusted_pending": 0.0, "immature": 0.0 }
+                    },
+                    "error": null,
+                    "id": 1
+                })
+                .to_string(),
+            )
+            .create();
+
+        let client = SyscoinClient::new(
+            &mock_server.url(),
+            "user",
+            "password",
+            "http://poda.example.com",
+            None,
+            "test_wallet",
+        )
+        .unwrap();
+
+        let balances = client.get_balances().await.unwrap();
+        assert_eq!(balances.mine.trusted, 1.5);
+        assert_eq!(balances.mine.untrusted_pending, 0.25);
+        assert_eq!(balances.watchonly.unwrap().trusted, 2.0);
+    }
+
+    #[tokio::test]
+    async fn test_balance_monitor_emits_threshold_events_once() {
+        let mut mock_server = std::thread::spawn(|| Server::new())
+            .join()
+            .expect("Failed to create mock server");
+
+        // 2 sat per blob byte
+        mock_blob_base_fee(&mut mock_server, 0.002);
+        mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(mockito::Matcher::Regex("getbalances".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "result": {
+                        "mine": { "trusted": 0.0001, "untrusted_pending": 0.0, "immature": 0.0 }
+                    },
+                    "error": null,
+                    "id": 1
+                })
+                .to_string(),
+            )
+            .create();
+
+        let client = SyscoinClient::new(
+            &mock_server.url(),
+            "user",
+            "password",
+            "http://poda.example.com",
+            None,
+            "test_wallet",
+        )
+        .unwrap();
+
+        let mut seen = Vec::new();
+        let events = {
+            let mut monitor = BalanceMonitor::new(
+                &client,
+                BalanceMonitorConfig {
+                    blob_size: 1000,
+                    low_balance: Some(0.01),
+                    min_affordable_blobs: Some(10),
+                    ..Default::default()
+                },
+            );
+            monitor.on_event(|event| seen.push(event.clone()));
+
+            let first = monitor.poll().await.unwrap();
+            // 10_000 sat / (2 sat * 1000 bytes) = 5 blobs
+            assert_eq!(monitor.last_snapshot().unwrap().affordable_blobs, Some(5));
+            let second = monitor.poll().await.unwrap();
+            assert!(second.is_empty(), "Unchanged state should not re-emit: {:?}", second);
+            first
+        };
+
+        assert_eq!(events.len(), 3);
+        assert!(matches!(events[0], BalanceEvent::BalanceChanged { previous: None, .. }));
+        assert_eq!(events[1], BalanceEvent::LowFunds { trusted: 0.0001, threshold: 0.01 });
+        assert_eq!(
+            events[2],
+            BalanceEvent::LowBlobCapacity { affordable_blobs: 5, threshold: 10 }
+        );
+        assert_eq!(seen, events);
+    }
+
+    #[tokio::test]
+    async fn test_balance_monitor_survives_fee_errors() {
+        let mut mock_server = std::thread::spawn(|| Server::new())
+            .join()
+            .expect("Failed to create mock server");
+
+        mock_server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::Regex("estimatesmartfee".into()))
+            .with_status(500)
+            .with_body("node busy")
+            .create();
+        mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(mockito::Matcher::Regex("getbalances".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "result": {
+                        "mine": { "trusted": 1.5, "untrusted_pending": 0.0, "immature": 0.0 }
+                    },
+                    "error": null,
+                    "id": 1
+                })
+                .to_string(),
+            )
+            .create();
+
+        let client = SyscoinClient::new(
+            &mock_server.url(),
+            "user",
+            "password",
+            "http://poda.example.com",
+            None,
+            "test_wallet",
+        )
+        .unwrap();
+
+        let mut monitor = BalanceMonitor::new(
+            &client,
+            BalanceMonitorConfig {
+                poll_interval: Duration::from_millis(1),
+                min_affordable_blobs: Some(10),
+                ..Default::default()
+            },
+        );
+        let snapshot = monitor.wait_until(|s| s.balances.mine.trusted > 0.0).await.unwrap();
+        assert_eq!(snapshot.fee_per_byte, None);
+        assert_eq!(snapshot.affordable_blobs, None);
+    }
+
+    #[tokio::test]
+    async fn test_get_wallet_info() {
+        let mut mock_server = std::thread::spawn(|| Server::new())
+            .join()
+            .expect("Failed to create mock server");
+
+        mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(mockito::Matcher::Regex("getwalletinfo".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "result": {
+                        "walletname": "test_wallet",
+                        "walletversion": 169900,
+                        "format": "sqlite",
+                        "txcount": 12,
+                        "keypoolsize": 4000,
+                        "unlocked_until": 0,
+                        "paytxfee": 0.0,
+                        "private_keys_enabled": true,
+                        "avoid_reuse": false,
+                        "scanning": { "duration": 4, "progress": 0.5 },
+                        "descriptors": true
+                    },
+                    "error": null,
+                    "id": 1
+                })
+                .to_string(),
+            )
+            .create();
+
+        let client = SyscoinClient::new(
+            &mock_server.url(),
+            "user",
+            "password",
+            "http://poda.example.com",
+            None,
+            "test_wallet",
+        )
+        .unwrap();
+
+        let info = client.get_wallet_info().await.unwrap();
+        assert_eq!(info.wallet_name, "test_wallet");
+        assert_eq!(info.tx_count, 12);
+        assert!(info.is_encrypted());
+        assert!(info.descriptors);
+        assert_eq!(info.scanning, WalletScan::Running { duration: 4, progress: 0.5 });
+    }
+
+    #[tokio::test]
+    async fn test_list_and_unload_wallets() {
+        let mut mock_server = std::thread::spawn(|| Server::new())
+            .join()
+            .expect("Failed to create mock server");
+
+        mock_server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::Regex("listwallets".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"result": ["", "test_wallet"], "error": null, "id": 1}).to_string())
+            .create();
+        let unload = mock_server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::JsonString(
+                r#"{"jsonrpc":"2.0","id":1,"method":"unloadwallet","params":["test_wallet"]}"#.to_string(),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"result": {"warning": ""}, "error": null, "id": 1}).to_string())
+            .create();
+
+        let client = SyscoinClient::new(
+            &mock_server.url(),
+            "user",
+            "password",
+            "http://poda.example.com",
+            None,
+            "test_wallet",
+        )
+        .unwrap();
+
+        assert_eq!(client.list_wallets().await.unwrap(), vec!["", "test_wallet"]);
+        client.unload_wallet("test_wallet").await.unwrap();
+        unload.assert();
+    }
+
+    #[tokio::test]
+    async fn test_create_blob_unlocks_encrypted_wallet_and_relocks() {
+        let mut mock_server = std::thread::spawn(|| Server::new())
+            .join()
+            .expect("Failed to create mock server");
+
+        // First submission hits a locked wallet, the retry succeeds.
+        let locked = mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(mockito::Matcher::Regex("syscoincreatenevmblob".into()))
+            .with_status(500)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "result": null,
+                    "error": {
+                        "code": -13,
+                        "message": "Error: Please enter the wallet passphrase with walletpassphrase first."
+                    },
+                    "id": 1
+                })
+                .to_string(),
+            )
+            .expect(1)
+            .create();
+        let submitted = mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(mockito::Matcher::Regex("syscoincreatenevmblob".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"result": {"versionhash": "deadbeef"}, "error": null, "id": 1}).to_string())
+            .expect(1)
+            .create();
+        let unlock = mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(mockito::Matcher::JsonString(
+                r#"{"jsonrpc":"2.0","id":1,"method":"walletpassphrase","params":["hunter2",5]}"#.to_string(),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"result": null, "error": null, "id": 1}).to_string())
+            .expect(1)
+            .create();
+        let relock = mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(mockito::Matcher::Regex("walletlock".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"result": null, "error": null, "id": 1}).to_string())
+            .expect(1)
+            .create();
+
+        let client = SyscoinClient::new(
+            &mock_server.url(),
+            "user",
+            "password",
+            "http://poda.example.com",
+            None,
+            "test_wallet",
+        )
+        .unwrap()
+        .with_wallet_passphrase(SecretString::from("hunter2"), Some(Duration::from_secs(5)));
+
+        let result = client.create_blob(&[1, 2, 3, 4]).await;
+        assert_eq!(result.unwrap(), "deadbeef");
+        locked.assert();
+        submitted.assert();
+        unlock.assert();
+        relock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_locked_wallet_without_passphrase_surfaces_rpc_code() {
+        let mut mock_server = std::thread::spawn(|| Server::new())
+            .join()
+            .expect("Failed to create mock server");
+
+        mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .with_status(500)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "result": null,
+                    "error": { "code": -13, "message": "Please enter the wallet passphrase" },
+                    "id": 1
+                })
+                .to_string(),
+            )
+            .create();
+
+        let client = SyscoinClient::new(
+            &mock_server.url(),
+            "user",
+            "password",
+            "http://poda.example.com",
+            None,
+            "test_wallet",
+        )
+        .unwrap();
+
+        let err = client.create_blob(&[1, 2, 3, 4]).await.unwrap_err();
+        assert_eq!(rpc_error_code(&err), Some(-13));
+    }
+
+    #[tokio::test]
+    async fn test_create_or_load_wallet_creates_on_not_found_with_options() {
+        let mut mock_server = std::thread::spawn(|| Server::new())
+            .join()
+            .expect("Failed to create mock server");
+
+        mock_server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::Regex("loadwallet".into()))
+            .with_status(500)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "result": null,
+                    "error": {
+                        "code": -18,
+                        "message": "Wallet file verification failed. Path does not exist."
+                    },
+                    "id": 1
+                })
+                .to_string(),
+            )
+            .create();
+        let create = mock_server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::JsonString(
+                r#"{"jsonrpc":"2.0","id":1,"method":"createwallet","params":["monitor",true,false,"",false,true,true]}"#
+                    .to_string(),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"result": {"name": "monitor", "warning": ""}, "error": null, "id": 1}).to_string())
+            .expect(1)
+            .create();
+
+        let client = SyscoinClient::new(
+            &mock_server.url(),
+            "user",
+            "password",
+            "http://poda.example.com",
+            None,
+            "monitor",
+        )
+        .unwrap();
+
+        let options = CreateWalletOptions {
+            descriptors: Some(true),
+            disable_private_keys: true,
+            load_on_startup: Some(true),
+            ..Default::default()
+        };
+        let result = client.create_or_load_wallet_with_options("monitor", &options).await;
+        assert!(result.is_ok(), "Error: {:?}", result.err());
+        create.assert();
+    }
+
+    #[tokio::test]
+    async fn test_create_or_load_wallet_already_loaded_and_other_errors() {
+        let mut mock_server = std::thread::spawn(|| Server::new())
+            .join()
+            .expect("Failed to create mock server");
+
+        mock_server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::Regex("already_loaded".into()))
+            .with_status(500)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "result": null,
+                    "error": { "code": -35, "message": "Wallet \"already_loaded\" is already loaded." },
+                    "id": 1
+                })
+                .to_string(),
+            )
+            .create();
+        // A generic failure whose message mentions "failed" must not trigger createwallet
+        mock_server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::Regex("corrupt".into()))
+            .with_status(500)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "result": null,
+                    "error": { "code": -4, "message": "Wallet loading failed: corrupt database" },
+                    "id": 1
+                })
+                .to_string(),
+            )
+            .create();
+        let create = mock_server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::Regex("createwallet".into()))
+            .expect(0)
+            .create();
+
+        let client = SyscoinClient::new(
+            &mock_server.url(),
+            "user",
+            "password",
+            "http://poda.example.com",
+            None,
+            "test_wallet",
+        )
+        .unwrap();
+
+        assert!(client.create_or_load_wallet("already_loaded").await.is_ok());
+        let err = client.create_or_load_wallet("corrupt").await.unwrap_err();
+        assert_eq!(rpc_error_code(&err), Some(-4));
+        create.assert();
+    }
+
+    fn mock_list_unspent(mock_server: &mut mockito::ServerGuard) {
+        mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(mockito::Matcher::Regex("listunspent".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "result": [
+                        { "txid": "aa", "vout": 0, "amount": 0.5, "confirmations": 10, "spendable": true, "solvable": true, "safe": true },
+                        { "txid": "bb", "vout": 1, "amount": 0.0001, "confirmations": 3, "spendable": true, "solvable": true, "safe": true },
+                        { "txid": "cc", "vout": 0, "amount": 0.0002, "confirmations": 2, "spendable": true, "solvable": true, "safe": true },
+                        { "txid": "dd", "vout": 2, "amount": 0.1, "confirmations": 0, "ancestorcount": 25, "spendable": true, "solvable": true, "safe": true }
+                    ],
+                    "error": null,
+                    "id": 1
+                })
+                .to_string(),
+            )
+            .create();
+        mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(mockito::Matcher::Regex("getrawchangeaddress".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"result": "sys1qchange", "error": null, "id": 1}).to_string())
+            .create();
+    }
+
+    #[tokio::test]
+    async fn test_consolidate_utxos_merges_smallest() {
+        let mut mock_server = std::thread::spawn(|| Server::new())
+            .join()
+            .expect("Failed to create mock server");
+
+        mock_list_unspent(&mut mock_server);
+        let send = mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(mockito::Matcher::PartialJson(json!({
+                "method": "send",
+                "params": [
+                    [{ "sys1qchange": "0.00030000" }],
+                    null,
+                    null,
+                    2.0,
+                    {
+                        "inputs": [{ "txid": "bb", "vout": 1 }, { "txid": "cc", "vout": 0 }],
+                        "add_inputs": false,
+                        "subtract_fee_from_outputs": [0]
+                    }
+                ]
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"result": {"txid": "feed", "complete": true}, "error": null, "id": 1}).to_string())
+            .expect(1)
+            .create();
+
+        let client = SyscoinClient::new(
+            &mock_server.url(),
+            "user",
+            "password",
+            "http://poda.example.com",
+            None,
+            "test_wallet",
+        )
+        .unwrap();
+
+        let utxos = client.list_unspent(0).await.unwrap();
+        assert_eq!(utxos.len(), 4);
+        assert!(!utxos[3].is_usable(25), "Output at the ancestor limit is not usable");
+
+        // the mock returns all four outputs, so merging down to 3 takes the two smallest
+        let result = client.consolidate_utxos(3, Some(2.0)).await.unwrap().unwrap();
+        assert_eq!(result.txid, "feed");
+        assert_eq!(result.inputs, 2);
+        send.assert();
+
+        assert!(client.consolidate_utxos(10, None).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_create_blob_preflight_splits_when_utxos_run_low() {
+        let mut mock_server = std::thread::spawn(|| Server::new())
+            .join()
+            .expect("Failed to create mock server");
+
+        mock_list_unspent(&mut mock_server);
+        let split = mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(mockito::Matcher::PartialJson(json!({
+                "method": "send",
+                "params": [[
+                    { "sys1qchange": "0.12500000" },
+                    { "sys1qchange": "0.12500000" },
+                    { "sys1qchange": "0.12500000" },
+                    { "sys1qchange": "0.12500000" }
+                ]]
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"result": {"txid": "split", "complete": true}, "error": null, "id": 1}).to_string())
+            .expect(1)
+            .create();
+        mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(mockito::Matcher::Regex("syscoincreatenevmblob".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"result": {"versionhash": "deadbeef"}, "error": null, "id": 1}).to_string())
+            .create();
+
+        let client = SyscoinClient::new(
+            &mock_server.url(),
+            "user",
+            "password",
+            "http://poda.example.com",
+            None,
+            "test_wallet",
+        )
+        .unwrap()
+        .with_utxo_preflight(UtxoPreflight {
+            min_usable: 4,
+            action: PreflightAction::Split { outputs: 4 },
+            ..Default::default()
+        });
+
+        assert_eq!(client.create_blob(&[1, 2, 3, 4]).await.unwrap(), "deadbeef");
+        split.assert();
+    }
+
+    #[tokio::test]
+    async fn test_bump_blob_fee_falls_back_to_psbt() {
+        let mut mock_server = std::thread::spawn(|| Server::new())
+            .join()
+            .expect("Failed to create mock server");
+
+        mock_server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::Regex("getnevmblobdata".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"result": {"versionhash": "feedbeef", "txid": "aa"}, "error": null, "id": 1}).to_string())
+            .create();
+        mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(mockito::Matcher::JsonString(
+                r#"{"jsonrpc":"2.0","id":1,"method":"bumpfee","params":["aa",{"fee_rate":12.5}]}"#.to_string(),
+            ))
+            .with_status(500)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "result": null,
+                    "error": {
+                        "code": -4,
+                        "message": "bumpfee is not available with wallets that have private keys disabled. Use psbtbumpfee instead."
+                    },
+                    "id": 1
+                })
+                .to_string(),
+            )
+            .create();
+        mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(mockito::Matcher::Regex("psbtbumpfee".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({"result": {"psbt": "cHNidP8=", "origfee": 0.0001, "fee": 0.0003}, "error": null, "id": 1})
+                    .to_string(),
+            )
+            .create();
+
+        let client = SyscoinClient::new(
+            &mock_server.url(),
+            "user",
+            "password",
+            "http://poda.example.com",
+            None,
+            "test_wallet",
+        )
+        .unwrap();
+
+        let result = client.bump_blob_fee("0xfeedbeef", Some(12.5)).await.unwrap();
+        assert_eq!(result.original_txid, "aa");
+        assert_eq!(result.txid, None);
+        assert_eq!(result.psbt.as_deref(), Some("cHNidP8="));
+        assert_eq!(result.fee, 0.0003);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_blob_finality_bumps_stuck_transaction() {
+        let mut mock_server = std::thread::spawn(|| Server::new())
+            .join()
+            .expect("Failed to create mock server");
+
+        // finality check and bump lookup of the first poll see an unconfirmed blob
+        mock_server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::Regex("getnevmblobdata".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({"result": {"versionhash": "feedbeef", "txid": "aa", "chainlock": false}, "error": null, "id": 1})
+                    .to_string(),
+            )
+            .expect(2)
+            .create();
+        mock_server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::Regex("getnevmblobdata".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "result": {"versionhash": "feedbeef", "txid": "bb", "height": 101, "chainlock": true},
+                    "error": null,
+                    "id": 1
+                })
+                .to_string(),
+            )
+            .create();
+        mock_server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::Regex("getblockcount".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"result": 100, "error": null, "id": 1}).to_string())
+            .create();
+        let bump = mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(mockito::Matcher::JsonString(
+                r#"{"jsonrpc":"2.0","id":1,"method":"bumpfee","params":["aa",{"fee_rate":5.0}]}"#.to_string(),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({"result": {"txid": "bb", "origfee": 0.0001, "fee": 0.0005, "errors": []}, "error": null, "id": 1})
+                    .to_string(),
+            )
+            .expect(1)
+            .create();
+
+        let client = SyscoinClient::new(
+            &mock_server.url(),
+            "user",
+            "password",
+            "http://poda.example.com",
+            None,
+            "test_wallet",
+        )
+        .unwrap();
+
+        let options = FinalityWaitOptions {
+            poll_interval: Duration::from_millis(10),
+            timeout: Some(Duration::from_secs(5)),
+            fee_bump: Some(FeeBumpPolicy {
+                after_blocks: 0,
+                fee_rate: Some(5.0),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let outcome = client.wait_for_blob_finality("feedbeef", &options).await.unwrap();
+        assert_eq!(outcome.txid.as_deref(), Some("bb"));
+        assert_eq!(outcome.fee_bumps, 1);
+        bump.assert();
+    }
+
+    #[tokio::test]
+    async fn test_send_to_address_and_dry_run() {
+        let mut mock_server = std::thread::spawn(|| Server::new())
+            .join()
+            .expect("Failed to create mock server");
+
+        let send = mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(mockito::Matcher::PartialJson(json!({
+                "method": "send",
+                "params": [[{ "sys1qdest": "1.25000000" }], null, null, 3.0, { "subtract_fee_from_outputs": [0] }]
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"result": {"txid": "cafe", "complete": true}, "error": null, "id": 1}).to_string())
+            .expect(1)
+            .create();
+        mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(mockito::Matcher::Regex("gettransaction".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"result": {"txid": "cafe", "fee": -0.00000420}, "error": null, "id": 1}).to_string())
+            .create();
+        let dry_run = mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(mockito::Matcher::PartialJson(json!({
+                "method": "walletcreatefundedpsbt",
+                "params": [[], [{ "sys1qdest": "1.25000000" }], 0, {}]
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"result": {"psbt": "cHNidP8=", "fee": 0.00000300, "changepos": 1}, "error": null, "id": 1}).to_string())
+            .expect(1)
+            .create();
+
+        let client = SyscoinClient::new(
+            &mock_server.url(),
+            "user",
+            "password",
+            "http://poda.example.com",
+            None,
+            "test_wallet",
+        )
+        .unwrap();
+
+        let amount = Amount::from_sat(125_000_000);
+        let sent = client
+            .send_to_address(
+                "sys1qdest",
+                amount,
+                &SendOptions { fee_rate: Some(3.0), subtract_fee_from_amount: true, dry_run: false },
+            )
+            .await
+            .unwrap();
+        assert_eq!(sent.txid.as_deref(), Some("cafe"));
+        assert_eq!(sent.fee, Some(Amount::from_sat(420)));
+
+        let estimate = client
+            .send_to_address("sys1qdest", amount, &SendOptions { dry_run: true, ..Default::default() })
+            .await
+            .unwrap();
+        assert!(estimate.dry_run);
+        assert_eq!(estimate.txid, None);
+        assert_eq!(estimate.fee, Some(Amount::from_sat(300)));
+        send.assert();
+        dry_run.assert();
+    }
+
+    #[tokio::test]
+    async fn test_sweep_wallet_spends_all_outputs() {
+        let mut mock_server = std::thread::spawn(|| Server::new())
+            .join()
+            .expect("Failed to create mock server");
+
+        mock_list_unspent(&mut mock_server);
+        let sweep = mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(mockito::Matcher::PartialJson(json!({
+                "method": "send",
+                "params": [
+                    [{ "sys1qdest": "0.60030000" }],
+                    null,
+                    null,
+                    null,
+                    { "add_inputs": false, "subtract_fee_from_outputs": [0] }
+                ]
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"result": {"txid": "sweep", "complete": true}, "error": null, "id": 1}).to_string())
+            .expect(1)
+            .create();
+
+        let client = SyscoinClient::new(
+            &mock_server.url(),
+            "user",
+            "password",
+            "http://poda.example.com",
+            None,
+            "test_wallet",
+        )
+        .unwrap();
+
+        let result = client.sweep_wallet("sys1qdest", &SendOptions::default()).await.unwrap();
+        assert_eq!(result.txid.as_deref(), Some("sweep"));
+        assert_eq!(result.amount, Amount::from_sat(60_030_000));
+        sweep.assert();
+    }
+
+    #[tokio::test]
+    async fn test_get_blob_transaction() {
+        let mut mock_server = std::thread::spawn(|| Server::new())
+            .join()
+            .expect("Failed to create mock server");
+
+        let versionhash = "01a2b3c4";
+        mock_server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::Regex("getnevmblobdata".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "result": { "versionhash": versionhash, "txid": "cafe", "blockhash": "00ff", "height": 100 },
+                    "error": null,
+                    "id": 1
+                })
+                .to_string(),
+            )
+            .create();
+        mock_server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::JsonString(
+                r#"{"jsonrpc":"2.0","id":1,"method":"getrawtransaction","params":["cafe",true,"00ff"]}"#.to_string(),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "result": {
+                        "txid": "cafe",
+                        "version": 137,
+                        "vsize": 180,
+                        "vin": [{ "txid": "beef", "vout": 1, "sequence": 4294967293u32 }],
+                        "vout": [
+                            {
+                                "value": 0.0,
+                                "n": 0,
+                                "scriptPubKey": { "asm": format!("OP_RETURN {}", versionhash), "hex": format!("6a04{}", versionhash), "type": "nulldata" }
+                            },
+                            {
+                                "value": 0.4999,
+                                "n": 1,
+                                "scriptPubKey": { "asm": "0 abcd", "hex": "0014abcd", "type": "witness_v0_keyhash", "address": "sys1qchange" }
+                            }
+                        ],
+                        "blockhash": "00ff",
+                        "confirmations": 3
+                    },
+                    "error": null,
+                    "id": 1
+                })
+                .to_string(),
+            )
+            .create();
+        mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(mockito::Matcher::Regex("gettransaction".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"result": {"txid": "cafe", "fee": -0.0001}, "error": null, "id": 1}).to_string())
+            .create();
+
+        let client = SyscoinClient::new(
+            &mock_server.url(),
+            "user",
+            "password",
+            "http://poda.example.com",
+            None,
+            "test_wallet",
+        )
+        .unwrap();
+
+        let tx = client.get_blob_transaction(versionhash).await.unwrap();
+        assert_eq!(tx.txid, "cafe");
+        assert_eq!(tx.version, 137);
+        assert_eq!(tx.vsize, 180);
+        assert_eq!(tx.inputs[0].txid.as_deref(), Some("beef"));
+        assert_eq!(tx.outputs.len(), 2);
+        assert_eq!(tx.outputs[1].value, Amount::from_sat(49_990_000));
+        assert_eq!(tx.fee, Some(Amount::from_sat(10_000)));
+        assert_eq!(tx.block_hash.as_deref(), Some("00ff"));
+        assert_eq!(tx.confirmations, 3);
+        let commitment = tx.commitment.unwrap();
+        assert_eq!(commitment.output_index, 0);
+        assert_eq!(commitment.data, versionhash);
+    }
+
+    #[tokio::test]
+    async fn test_address_label_management() {
+        let mut mock_server = std::thread::spawn(|| Server::new())
+            .join()
+            .expect("Failed to create mock server");
+
+        mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(mockito::Matcher::Regex("getaddressesbylabel.*da_funding".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "result": {
+                        "sys1qzzz": { "purpose": "receive" },
+                        "sys1qaaa": { "purpose": "send" },
+                        "sys1qmmm": { "purpose": "receive" }
+                    },
+                    "error": null,
+                    "id": 1
+                })
+                .to_string(),
+            )
+            .create();
+        mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(mockito::Matcher::Regex("getaddressesbylabel.*unused".into()))
+            .with_status(500)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({"result": null, "error": {"code": -11, "message": "No addresses with label unused"}, "id": 1})
+                    .to_string(),
+            )
+            .create();
+        let new_address = mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(mockito::Matcher::JsonString(
+                r#"{"jsonrpc":"2.0","id":1,"method":"getnewaddress","params":["unused","bech32m"]}"#.to_string(),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"result": "sys1pnew", "error": null, "id": 1}).to_string())
+            .expect(1)
+            .create();
+        mock_server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::Regex("validateaddress".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "result": { "isvalid": true, "address": "sys1pnew", "isscript": true, "iswitness": true, "witness_version": 1 },
+                    "error": null,
+                    "id": 1
+                })
+                .to_string(),
+            )
+            .create();
+
+        let client = SyscoinClient::new(
+            &mock_server.url(),
+            "user",
+            "password",
+            "http://poda.example.com",
+            None,
+            "test_wallet",
+        )
+        .unwrap();
+
+        let addresses = client.list_addresses_by_label("da_funding").await.unwrap();
+        let listed: Vec<&str> = addresses.iter().map(|a| a.address.as_str()).collect();
+        assert_eq!(listed, vec!["sys1qaaa", "sys1qmmm", "sys1qzzz"]);
+        assert_eq!(addresses[0].purpose, AddressPurpose::Send);
+        assert_eq!(
+            client.fetch_address_by_label("da_funding").await.unwrap().as_deref(),
+            Some("sys1qmmm")
+        );
+
+        assert!(client.list_addresses_by_label("unused").await.unwrap().is_empty());
+        let created = client
+            .ensure_address_by_label_with_type("unused", AddressType::Bech32m)
+            .await
+            .unwrap();
+        assert_eq!(created, "sys1pnew");
+        new_address.assert();
+
+        let validation = client.validate_address("sys1pnew").await.unwrap();
+        assert!(validation.is_valid);
+        assert_eq!(validation.witness_version, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_blob_envelope_round_trip() {
+        use mockito::Matcher;
+
+        let mut mock_server = std::thread::spawn(|| {
+            Server::new()
+        }).join().expect("Failed to create mock server");
+
+        let payload = br#"{"height":42}"#.to_vec();
+        let mut options = EnvelopeOptions {
+            content_type: Some("application/json".into()),
+            namespace: Some("rollup-a".into()),
+            ..Default::default()
+        };
+        options.metadata.insert("batch".into(), "7".into());
+        let enveloped = bitcoin_da_client::encode_envelope(&payload, &options).unwrap();
+        assert!(enveloped.starts_with(bitcoin_da_client::ENVELOPE_MAGIC));
+
+        let create = mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(Matcher::Regex(format!(r#""params":\["{}""#, hex::encode(&enveloped))))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"result": {"versionhash": "abc123"}, "error": null, "id": 1}).to_string())
+            .create();
+        mock_server
+            .mock("POST", "/")
+            .match_body(Matcher::Regex(r#""params":\["abc123""#.into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"result": {"data": hex::encode(&enveloped)}, "error": null, "id": 1}).to_string())
+            .create();
+        mock_server
+            .mock("POST", "/")
+            .match_body(Matcher::Regex(r#""params":\["raw""#.into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"result": {"data": hex::encode(b"SDAE but not an envelope")}, "error": null, "id": 1}).to_string())
+            .create();
+
+        let client = SyscoinClient::new(
+            &mock_server.url(),
+            "user",
+            "password",
+            "http://poda.example.com",
+            None,
+            "test_wallet",
+        )
+        .unwrap();
+
+        let id = client.create_blob_with_envelope(&payload, &options).await.unwrap();
+        assert_eq!(id, "abc123");
+        create.assert();
+
+        let decoded = client.get_blob_decoded(&id).await.unwrap();
+        let header = decoded.header.expect("envelope header");
+        assert_eq!(decoded.data, payload);
+        assert_eq!(header.original_len, payload.len() as u64);
+        assert_eq!(header.content_type.as_deref(), Some("application/json"));
+        assert_eq!(header.namespace.as_deref(), Some("rollup-a"));
+        assert_eq!(header.metadata.get("batch").map(String::as_str), Some("7"));
+
+        let raw = client.get_blob_decoded("raw").await.unwrap();
+        assert!(raw.header.is_none());
+        assert_eq!(raw.data, b"SDAE but not an envelope");
+    }
+
+    /// Store whatever `syscoincreatenevmblob` receives (as hex) and serve it back from `getnevmblobdata`
+    fn mock_blob_echo(mock_server: &mut mockito::ServerGuard) -> std::sync::Arc<std::sync::Mutex<String>> {
+        let stored = std::sync::Arc::new(std::sync::Mutex::new(String::new()));
+        let submitted = stored.clone();
+        mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(mockito::Matcher::PartialJson(json!({"method": "syscoincreatenevmblob"})))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body_from_request(move |req| {
+                let body: serde_json::Value = serde_json::from_slice(req.body().unwrap()).unwrap();
+                *submitted.lock().unwrap() = body["params"][0].as_str().unwrap().to_owned();
+                json!({"result": {"versionhash": "cafe"}, "error": null, "id": 1}).to_string().into()
+            })
+            .create();
+        let fetched = stored.clone();
+        mock_server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::PartialJson(json!({"method": "getnevmblobdata"})))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body_from_request(move |_| {
+                let data = fetched.lock().unwrap().clone();
+                json!({"result": {"data": data}, "error": null, "id": 1}).to_string().into()
+            })
+            .create();
+        stored
+    }
+
+    /// Keep submitted blobs (as hex) under ids `blob0`, `blob1`, ... and serve them back;
+    /// ids missing from the map answer with a "not found" RPC error
+    fn mock_blob_store(
+        mock_server: &mut mockito::ServerGuard,
+    ) -> std::sync::Arc<std::sync::Mutex<std::collections::HashMap<String, String>>> {
+        let store = std::sync::Arc::new(std::sync::Mutex::new(std::collections::HashMap::new()));
+        let counter = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let submitted = store.clone();
+        mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(mockito::Matcher::PartialJson(json!({"method": "syscoincreatenevmblob"})))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body_from_request(move |req| {
+                let body: serde_json::Value = serde_json::from_slice(req.body().unwrap()).unwrap();
+                let id = format!("blob{}", counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst));
+                let data = body["params"][0].as_str().unwrap().to_owned();
+                submitted.lock().unwrap().insert(id.clone(), data);
+                json!({"result": {"versionhash": id}, "error": null, "id": 1}).to_string().into()
+            })
+            .create();
+        let fetched = store.clone();
+        mock_server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::PartialJson(json!({"method": "getnevmblobdata"})))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body_from_request(move |req| {
+                let body: serde_json::Value = serde_json::from_slice(req.body().unwrap()).unwrap();
+                let id = body["params"][0].as_str().unwrap();
+                match fetched.lock().unwrap().get(id) {
+                    Some(data) => json!({"result": {"data": data}, "error": null, "id": 1}),
+                    None => json!({"result": null, "error": {"code": -32602, "message": "Could not find blob information"}, "id": 1}),
+                }
+                .to_string()
+                .into()
+            })
+            .create();
+        store
+    }
+
+    #[tokio::test]
+    async fn test_compressed_blob_round_trip() {
+        let mut mock_server = std::thread::spawn(|| {
+            Server::new()
+        }).join().expect("Failed to create mock server");
+
+        let stored = mock_blob_echo(&mut mock_server);
+
+        let payload = b"{\"tx\":\"0000000000000000\"},".repeat(400);
+        let plain = SyscoinClient::new(&mock_server.url(), "user", "password", "http://poda.example.com", None, "test_wallet")
+            .unwrap();
+        let submission = plain.create_blob_with_report(&payload).await.unwrap();
+        assert_eq!(submission.compression.codec, Codec::None);
+        assert_eq!(submission.compression.stored_size, payload.len());
+        assert_eq!(*stored.lock().unwrap(), hex::encode(&payload));
+
+        #[allow(unused_mut)]
+        let mut codecs: Vec<Codec> = Vec::new();
+        #[cfg(feature = "zstd")]
+        codecs.push(Codec::Zstd);
+        #[cfg(feature = "brotli")]
+        codecs.push(Codec::Brotli);
+        #[cfg(feature = "lz4")]
+        codecs.push(Codec::Lz4);
+
+        for codec in codecs {
+            let client = SyscoinClient::new(&mock_server.url(), "user", "password", "http://poda.example.com", None, "test_wallet")
+                .unwrap()
+                .with_compression(CompressionConfig::new(codec));
+            let submission = client.create_blob_with_report(&payload).await.unwrap();
+            assert_eq!(submission.versionhash, "cafe");
+            assert_eq!(submission.compression.codec, codec);
+            assert!(submission.compression.ratio() < 0.5, "{:?}", submission.compression);
+
+            assert_eq!(client.get_blob("cafe").await.unwrap(), payload);
+            // readers don't need compression configured
+            assert_eq!(plain.get_blob("cafe").await.unwrap(), payload);
+            let decoded = plain.get_blob_decoded("cafe").await.unwrap();
+            assert_eq!(decoded.header.unwrap().codec, codec);
+
+            // tiny payloads stay raw
+            let small = client.create_blob_with_report(b"tiny").await.unwrap();
+            assert_eq!(small.compression.codec, Codec::None);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_decompression_bomb_is_rejected() {
+        let mut mock_server = std::thread::spawn(|| {
+            Server::new()
+        }).join().expect("Failed to create mock server");
+
+        // zstd envelope claiming a terabyte of output
+        let mut blob = bitcoin_da_client::ENVELOPE_MAGIC.to_vec();
+        blob.extend_from_slice(&[bitcoin_da_client::ENVELOPE_VERSION, 1]);
+        blob.extend_from_slice(&(1u64 << 40).to_le_bytes());
+        blob.extend_from_slice(&[0, 0, 0, 0, 0, 0]);
+        blob.extend_from_slice(&[0x28, 0xb5, 0x2f, 0xfd]);
+        mock_server
+            .mock("POST", "/")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"result": {"data": hex::encode(&blob)}, "error": null, "id": 1}).to_string())
+            .create();
+
+        let client = SyscoinClient::new(&mock_server.url(), "user", "password", "http://poda.example.com", None, "test_wallet")
+            .unwrap()
+            .with_compression(CompressionConfig { max_decompressed_size: 1 << 20, ..Default::default() });
+
+        let err = client.get_blob("bomb").await.unwrap_err();
+        assert!(err.to_string().contains("limit"), "{}", err);
+    }
+
+    #[cfg(feature = "encryption")]
+    #[tokio::test]
+    async fn test_encrypted_blob_round_trip() {
+        use bitcoin_da_client::{BlobKey, DecryptionKey, EncryptionKey, X25519PublicKey, X25519StaticSecret};
+
+        let mut mock_server = std::thread::spawn(|| {
+            Server::new()
+        }).join().expect("Failed to create mock server");
+        let stored = mock_blob_echo(&mut mock_server);
+
+        let client = SyscoinClient::new(&mock_server.url(), "user", "password", "http://poda.example.com", None, "test_wallet")
+            .unwrap();
+        let secret = b"sealed bid: 1200 SYS".to_vec();
+
+        let key = BlobKey::generate();
+        let id = client
+            .create_encrypted_blob(&secret, &EncryptionKey::Shared(key.clone()))
+            .await
+            .unwrap();
+        assert!(!stored.lock().unwrap().contains(&hex::encode(&secret)));
+        let opened = client.get_decrypted_blob(&id, &DecryptionKey::Shared(key.clone())).await.unwrap();
+        assert_eq!(opened, secret);
+        let wrong = client.get_decrypted_blob(&id, &DecryptionKey::Shared(BlobKey::generate())).await;
+        assert!(wrong.is_err());
+
+        // an uncompressed envelope comes back as written, as `get_blob` returns it
+        let enveloped = bitcoin_da_client::encode_envelope(&secret, &Default::default()).unwrap();
+        let id = client.create_encrypted_blob(&enveloped, &EncryptionKey::Shared(key.clone())).await.unwrap();
+        assert_eq!(client.get_decrypted_blob(&id, &DecryptionKey::Shared(key.clone())).await.unwrap(), enveloped);
+
+        let alice = X25519StaticSecret::from([1u8; 32]);
+        let bob = X25519StaticSecret::from([2u8; 32]);
+        let mallory = X25519StaticSecret::from([3u8; 32]);
+        let recipients = EncryptionKey::Recipients(vec![X25519PublicKey::from(&alice), X25519PublicKey::from(&bob)]);
+        let id = client.create_encrypted_blob(&secret, &recipients).await.unwrap();
+        assert_eq!(
+            stored.lock().unwrap().len() / 2,
+            secret.len() + bitcoin_da_client::encryption_overhead(2)
+        );
+        for reader in [alice, bob] {
+            let opened = client.get_decrypted_blob(&id, &DecryptionKey::Recipient(reader)).await.unwrap();
+            assert_eq!(opened, secret);
+        }
+        let err = client
+            .get_decrypted_blob(&id, &DecryptionKey::Recipient(mallory))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not encrypted for this key"), "{}", err);
+
+        let too_big = vec![0u8; bitcoin_da_client::MAX_BLOB_SIZE];
+        let err = client
+            .create_encrypted_blob(&too_big, &EncryptionKey::Shared(BlobKey::generate()))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("exceeds maximum"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_blob_batcher_receipts_and_items() {
+        let mut mock_server = std::thread::spawn(|| {
+            Server::new()
+        }).join().expect("Failed to create mock server");
+        mock_blob_echo(&mut mock_server);
+
+        let client = SyscoinClient::new(&mock_server.url(), "user", "password", "http://poda.example.com", None, "test_wallet")
+            .unwrap();
+        let records: Vec<Vec<u8>> = (0..7).map(|i| format!("record-{}", i).into_bytes()).collect();
+
+        // room for five 8-byte records per batch
+        let mut batcher = BlobBatcher::new(&client).with_max_size(41 + 5 * 12);
+        let mut receipts = Vec::new();
+        for record in &records {
+            receipts.extend(batcher.push(record.clone()).await.unwrap());
+        }
+        assert_eq!(receipts.len(), 5);
+        assert_eq!(batcher.len(), 2);
+        receipts.extend(batcher.flush().await.unwrap());
+        assert!(batcher.is_empty());
+        assert!(batcher.flush().await.unwrap().is_empty());
+
+        assert_eq!(receipts.len(), records.len());
+        assert_eq!(receipts[0].item_count, 5);
+        assert_eq!(receipts[6].item_count, 2);
+        assert_ne!(receipts[0].merkle_root, receipts[6].merkle_root);
+        for (receipt, record) in receipts.iter().zip(&records) {
+            assert_eq!(receipt.blob_id, "cafe");
+            assert!(bitcoin_da_client::verify_item_inclusion(receipt, record));
+        }
+        assert!(!bitcoin_da_client::verify_item_inclusion(&receipts[1], &records[2]));
+        assert!(!bitcoin_da_client::verify_item_inclusion(&receipts[4], b"forged"));
+
+        // the echo mock serves the last batch: records 5 and 6
+        assert_eq!(client.get_blob_item("cafe", 1).await.unwrap(), records[6]);
+        assert!(client.get_blob_item("cafe", 2).await.is_err());
+
+        let err = batcher.push(vec![0u8; 200]).await.unwrap_err();
+        assert!(err.to_string().contains("does not fit"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_blob_batcher_keeps_items_when_publishing_fails() {
+        let mut mock_server = std::thread::spawn(|| {
+            Server::new()
+        }).join().expect("Failed to create mock server");
+        let failing = mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(mockito::Matcher::Regex("syscoincreatenevmblob".into()))
+            .with_status(500)
+            .with_body("node restarting")
+            .create();
+
+        let client = SyscoinClient::new(&mock_server.url(), "user", "password", "http://poda.example.com", None, "test_wallet")
+            .unwrap();
+        let records: Vec<Vec<u8>> = (0..7).map(|i| format!("record-{}", i).into_bytes()).collect();
+
+        let mut batcher = BlobBatcher::new(&client).with_max_size(41 + 5 * 12);
+        for record in &records[..5] {
+            assert!(batcher.push(record.clone()).await.unwrap().is_empty());
+        }
+        assert!(batcher.push(records[5].clone()).await.is_err());
+        assert!(batcher.push(records[6].clone()).await.is_err());
+        assert_eq!(batcher.len(), 7, "Nothing is dropped when publishing fails");
+
+        failing.remove();
+        mock_blob_echo(&mut mock_server);
+        let mut receipts = Vec::new();
+        while !batcher.is_empty() {
+            receipts.extend(batcher.flush().await.unwrap());
+        }
+        assert_eq!(receipts.len(), records.len());
+        assert_eq!((receipts[0].item_count, receipts[6].item_count), (5, 2));
+        for (receipt, record) in receipts.iter().zip(&records) {
+            assert!(bitcoin_da_client::verify_item_inclusion(receipt, record));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_erasure_coded_recovery() {
+        let mut mock_server = std::thread::spawn(|| {
+            Server::new()
+        }).join().expect("Failed to create mock server");
+        let store = mock_blob_store(&mut mock_server);
+
+        let client = SyscoinClient::new(&mock_server.url(), "user", "password", &mock_server.url(), None, "test_wallet")
+            .unwrap();
+        let archive: Vec<u8> = (0..10_000u32).map(|i| (i * 7 % 251) as u8).collect();
+        let config = ErasureConfig { data_shards: 4, parity_shards: 2 };
+
+        let coded = client.create_erasure_coded(&archive, &config).await.unwrap();
+        assert_eq!(coded.descriptor.shard_ids.len(), 6);
+        assert_eq!(coded.descriptor.shard_size, 2_500);
+        assert_eq!(coded.descriptor_id, "blob6");
+        assert_eq!(client.recover_erasure_coded(&coded.descriptor_id).await.unwrap(), archive);
+
+        // lose one data shard and corrupt another: two parity shards make up for both
+        {
+            let mut blobs = store.lock().unwrap();
+            blobs.remove("blob0");
+            blobs.insert("blob2".into(), hex::encode(vec![0u8; 2_500]));
+        }
+        assert_eq!(client.recover_erasure_coded(&coded.descriptor_id).await.unwrap(), archive);
+
+        store.lock().unwrap().remove("blob5");
+        let err = client.recover_erasure_coded(&coded.descriptor_id).await.unwrap_err();
+        assert!(err.to_string().contains("only 3 of 6 shards"), "{}", err);
+
+        let err = client.recover_erasure_coded("blob1").await.unwrap_err();
+        assert!(err.to_string().contains("not an erasure coding descriptor"), "{}", err);
+
+        // a 1 byte payload leaves three identical zero data shards; each is submitted once
+        store.lock().unwrap().clear();
+        let tiny = client.create_erasure_coded(&[7], &config).await.unwrap();
+        let ids = &tiny.descriptor.shard_ids;
+        assert_eq!((ids.len(), ids[1].as_str(), ids[2].as_str(), ids[3].as_str()), (6, "blob8", "blob8", "blob8"));
+        let distinct: std::collections::HashSet<_> = tiny.descriptor.shard_hashes.iter().collect();
+        assert_eq!(store.lock().unwrap().len(), distinct.len() + 1);
+        assert_eq!(client.recover_erasure_coded(&tiny.descriptor_id).await.unwrap(), vec![7]);
+    }
+
+    #[tokio::test]
+    async fn test_streamed_upload_extends_short_unlock() {
+        let mut mock_server = std::thread::spawn(|| {
+            Server::new()
+        }).join().expect("Failed to create mock server");
+        let store = mock_blob_store(&mut mock_server);
+        let unlocked_until = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + 3;
+        mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(mockito::Matcher::Regex("getwalletinfo".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "result": {
+                        "walletname": "test_wallet",
+                        "walletversion": 169900,
+                        "txcount": 1,
+                        "unlocked_until": unlocked_until,
+                        "private_keys_enabled": true
+                    },
+                    "error": null,
+                    "id": 1
+                })
+                .to_string(),
+            )
+            .create();
+        // unlocked for the 30s request timeout, then handed back the ~3s that were left
+        let extend = mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(mockito::Matcher::JsonString(
+                r#"{"jsonrpc":"2.0","id":1,"method":"walletpassphrase","params":["hunter2",30]}"#.to_string(),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"result": null, "error": null, "id": 1}).to_string())
+            .expect(1)
+            .create();
+        let restore = mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(mockito::Matcher::Regex(r#""walletpassphrase","params":\["hunter2",[1-3]\]"#.into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"result": null, "error": null, "id": 1}).to_string())
+            .expect(1)
+            .create();
+        let relock = mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(mockito::Matcher::Regex("walletlock".into()))
+            .expect(0)
+            .create();
+
+        let client = SyscoinClient::new(&mock_server.url(), "user", "password", &mock_server.url(), None, "test_wallet")
+            .unwrap()
+            .with_wallet_passphrase(SecretString::from("hunter2"), Some(Duration::from_secs(5)));
+        let id = client
+            .create_blob_from_reader(std::io::Cursor::new(vec![7u8; 64]), 64)
+            .await
+            .unwrap();
+        assert_eq!(store.lock().unwrap()[&id], hex::encode([7u8; 64]));
+        extend.assert();
+        restore.assert();
+        relock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_streaming_blob_upload_and_download() {
+        use tokio::io::AsyncReadExt;
+
+        let mut mock_server = std::thread::spawn(|| {
+            Server::new()
+        }).join().expect("Failed to create mock server");
+        let store = mock_blob_store(&mut mock_server);
+        let pruned = bitcoin_da_client::blob_versionhash(b"from the cloud");
+        let cloud = mock_server
+            .mock("GET", format!("/blob/{}", pruned).as_str())
+            .with_status(200)
+            .with_body(b"from the cloud")
+            .expect(2)
+            .create();
+        mock_server.mock("GET", "/blob/forged").with_status(200).with_body(b"from the cloud").create();
+
+        let client = SyscoinClient::new(&mock_server.url(), "user", "password", &mock_server.url(), None, "test_wallet")
+            .unwrap()
+            .with_versionhash_check(true);
+        let payload: Vec<u8> = (0..200_000u32).map(|i| (i % 253) as u8).collect();
+
+        let chunks: Vec<Result<bytes::Bytes, std::io::Error>> = payload
+            .chunks(7_919)
+            .map(|c| Ok(bytes::Bytes::copy_from_slice(c)))
+            .collect();
+        let id = client
+            .create_blob_from_stream(futures_util::stream::iter(chunks), payload.len())
+            .await
+            .unwrap();
+        assert_eq!(store.lock().unwrap()[&id], hex::encode(&payload));
+
+        let id = client
+            .create_blob_from_reader(std::io::Cursor::new(payload.clone()), payload.len())
+            .await
+            .unwrap();
+        assert_eq!(id, "blob1");
+
+        let mut stream = client.get_blob_stream(&id).await.unwrap();
+        let mut streamed = Vec::new();
+        while let Some(chunk) = futures_util::StreamExt::next(&mut stream).await {
+            streamed.extend_from_slice(&chunk.unwrap());
+        }
+        assert_eq!(streamed, payload);
+
+        let mut read_back = Vec::new();
+        client.get_blob_reader(&id).await.unwrap().read_to_end(&mut read_back).await.unwrap();
+        assert_eq!(read_back, payload);
+
+        // unknown to the node: served by PoDA cloud instead, checked as it streams
+        let mut cloud_bytes = Vec::new();
+        client.get_blob_reader(&pruned).await.unwrap().read_to_end(&mut cloud_bytes).await.unwrap();
+        assert_eq!(cloud_bytes, b"from the cloud");
+        let mut forged = Vec::new();
+        let err = client.get_blob_reader("forged").await.unwrap().read_to_end(&mut forged).await.unwrap_err();
+        assert!(err.to_string().contains("failed verification"), "{err}");
+        let vetoed = SyscoinClient::new(&mock_server.url(), "user", "password", &mock_server.url(), None, "test_wallet")
+            .unwrap()
+            .with_blob_verifier(std::sync::Arc::new(|_, data: &[u8]| data.len() > 100));
+        let mut stream = vetoed.get_blob_stream(&pruned).await.unwrap();
+        let mut last = None;
+        while let Some(chunk) = futures_util::StreamExt::next(&mut stream).await {
+            last = Some(chunk);
+        }
+        assert!(last.unwrap().is_err(), "The verifier sees the whole streamed blob");
+        cloud.assert();
+
+        let short = client
+            .create_blob_from_reader(std::io::Cursor::new(vec![1u8; 10]), 11)
+            .await;
+        assert!(short.is_err());
+        assert_eq!(store.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_blob_stream_follows_retrieval_strategy() {
+        async fn read(client: &SyscoinClient, id: &str) -> Result<Vec<u8>, bitcoin_da_client::SyscoinError> {
+            let mut stream = client.get_blob_stream(id).await?;
+            let mut data = Vec::new();
+            while let Some(chunk) = futures_util::StreamExt::next(&mut stream).await {
+                data.extend_from_slice(&chunk?);
+            }
+            Ok(data)
+        }
+
+        let mut mock_server = std::thread::spawn(|| {
+            Server::new()
+        }).join().expect("Failed to create mock server");
+        let store = mock_blob_store(&mut mock_server);
+        store.lock().unwrap().insert("blob0".into(), hex::encode(b"on chain"));
+        mock_server.mock("GET", "/blob/blob0").with_status(200).with_body("cloud copy").create();
+        let pruned = bitcoin_da_client::blob_versionhash(b"pruned");
+        let cloud = mock_server
+            .mock("GET", format!("/blob/{}", pruned).as_str())
+            .with_status(200)
+            .with_body("pruned")
+            .expect(1)
+            .create();
+
+        let client = |strategy| {
+            SyscoinClient::new(&mock_server.url(), "user", "password", &mock_server.url(), None, "test_wallet")
+                .unwrap()
+                .with_retrieval_strategy(strategy)
+        };
+
+        // validators reading RPC only never get cloud bytes
+        let rpc_only = client(RetrievalStrategy::RpcOnly);
+        assert_eq!(read(&rpc_only, "blob0").await.unwrap(), b"on chain");
+        assert!(read(&rpc_only, &pruned).await.is_err());
+
+        assert_eq!(read(&client(RetrievalStrategy::RpcThenCloud), "blob0").await.unwrap(), b"on chain");
+        assert_eq!(read(&client(RetrievalStrategy::RpcThenCloud), &pruned).await.unwrap(), b"pruned");
+        assert_eq!(read(&client(RetrievalStrategy::CloudThenRpc), "blob0").await.unwrap(), b"cloud copy");
+        assert_eq!(read(&client(RetrievalStrategy::CloudOnly), "blob0").await.unwrap(), b"cloud copy");
+        assert!(read(&client(RetrievalStrategy::CloudOnly), "blob1").await.is_err());
+        assert!(read(&client(RetrievalStrategy::Parallel), "blob0").await.is_ok());
+        cloud.assert();
+    }
+
+    #[tokio::test]
+    async fn test_blob_cache_serves_repeat_fetches() {
+        use bitcoin_da_client::{BlobCache, DiskBlobCache, MemoryBlobCache};
+        use std::sync::Arc;
+
+        let mut mock_server = std::thread::spawn(|| {
+            Server::new()
+        }).join().expect("Failed to create mock server");
+        let node = mock_server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::Regex(r#"(?i)"params":\["(aa01|bb02)""#.into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"result": {"data": hex::encode(vec![7u8; 600])}, "error": null, "id": 1}).to_string())
+            .expect(3)
+            .create();
+        mock_server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::Regex(r#""params":\["(cc03|dd04|[0-9a-f]{64})""#.into()))
+            .with_status(500)
+            .with_body(json!({"result": null, "error": {"code": -32602, "message": "Could not find blob information"}, "id": 1}).to_string())
+            .create();
+        let pruned = bitcoin_da_client::blob_versionhash(&[9u8; 100]);
+        let cloud = mock_server
+            .mock("GET", format!("/blob/{}", pruned).as_str())
+            .with_status(200)
+            .with_body(vec![9u8; 100])
+            .expect(2)
+            .create();
+        mock_server.mock("GET", "/blob/dd04").with_status(200).with_body(vec![9u8; 100]).create();
+
+        let memory = Arc::new(MemoryBlobCache::new(1_000));
+        let client = SyscoinClient::new(&mock_server.url(), "user", "password", &mock_server.url(), None, "test_wallet")
+            .unwrap()
+            .with_blob_cache(memory.clone());
+
+        assert_eq!(client.get_blob("0xAA01").await.unwrap(), vec![7u8; 600]);
+        assert_eq!(client.get_blob("aa01").await.unwrap(), vec![7u8; 600]);
+        let stats = memory.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries, stats.bytes), (1, 1, 1, 600));
+
+        // a second 600 byte blob pushes the first one out
+        client.get_blob("bb02").await.unwrap();
+        assert_eq!(memory.stats().evictions, 1);
+        client.get_blob("aa01").await.unwrap();
+        node.assert();
+
+        // unchecked cloud copies are served but not cached
+        assert_eq!(client.get_blob("dd04").await.unwrap(), vec![9u8; 100]);
+        assert!(memory.get("dd04").await.unwrap().is_none());
+        // cloud copies are only cached once verified
+        let client = client.with_versionhash_check(true);
+        assert!(client.get_blob("dd04").await.is_err(), "Bytes that do not hash to dd04 are rejected");
+        let strict = client.with_blob_verifier(Arc::new(|_, data: &[u8]| data.len() > 100));
+        assert!(strict.get_blob(&pruned).await.is_err(), "The verifier adds to the versionhash check");
+        let client = strict.with_blob_verifier(Arc::new(|_, data: &[u8]| data.len() == 100));
+        client.get_blob(&pruned).await.unwrap();
+        client.get_blob(&pruned).await.unwrap();
+        cloud.assert();
+
+        let dir = tempfile::tempdir().unwrap();
+        let disk = Arc::new(DiskBlobCache::open(dir.path(), 10_000).await.unwrap());
+        disk.put("cc03", &[9u8; 100]).await.unwrap();
+        drop(disk);
+
+        let reopened = DiskBlobCache::open(dir.path(), 10_000).await.unwrap();
+        assert_eq!(reopened.stats().entries, 1);
+        assert_eq!(reopened.get("cc03").await.unwrap(), Some(vec![9u8; 100]));
+        std::fs::write(dir.path().join("cc").join("cc03"), b"bit rot").unwrap();
+        assert_eq!(reopened.get("cc03").await.unwrap(), None);
+        assert_eq!(reopened.stats().entries, 0);
+        assert!(reopened.put("../escape", b"x").await.is_err());
+
+        // a hit refreshes the entry on disk, so it outlives newer but unused entries
+        let ages = [("aa01", 20), ("bb02", 10)];
+        for (id, _) in ages {
+            reopened.put(id, &[1u8; 100]).await.unwrap();
+        }
+        for (id, age) in ages {
+            std::fs::File::options()
+                .write(true)
+                .open(dir.path().join(&id[..2]).join(id))
+                .unwrap()
+                .set_modified(std::time::SystemTime::now() - Duration::from_secs(age))
+                .unwrap();
+        }
+        assert!(reopened.get("aa01").await.unwrap().is_some());
+        drop(reopened);
+        let shrunk = DiskBlobCache::open(dir.path(), 150).await.unwrap();
+        assert!(shrunk.get("aa01").await.unwrap().is_some());
+        assert!(shrunk.get("bb02").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_poda_mirrors_skip_bad_answers() {
+        use std::sync::Arc;
+
+        let id = bitcoin_da_client::blob_versionhash(b"genuine");
+        let path = format!("/blob/{}", id);
+        let mut servers = std::thread::spawn(|| {
+            (Server::new(), Server::new(), Server::new())
+        }).join().expect("Failed to create mock servers");
+        let (bad, slow, down) = (&mut servers.0, &mut servers.1, &mut servers.2);
+        bad.mock("GET", path.as_str()).with_status(200).with_body("forged").create();
+        slow.mock("GET", path.as_str())
+            .with_status(200)
+            .with_chunked_body(|w| {
+                std::thread::sleep(Duration::from_millis(200));
+                w.write_all(b"genuine")
+            })
+            .create();
+        down.mock("GET", path.as_str()).with_status(503).create();
+
+        let unchecked = |primary: &str, mirrors: Vec<String>, strategy| {
+            SyscoinClient::new("http://localhost:1", "user", "password", primary, None, "test_wallet")
+                .unwrap()
+                .with_poda_mirrors(mirrors, strategy)
+        };
+        // with no check configured the first answer is taken as is
+        let trusting = unchecked(&bad.url(), vec![slow.url()], MirrorStrategy::Sequential);
+        assert_eq!(trusting.get_blob_from_cloud(&id).await.unwrap(), b"forged");
+        let verified = trusting.with_blob_verifier(Arc::new(|_, data: &[u8]| data == b"genuine"));
+        assert_eq!(verified.get_blob_from_cloud(&id).await.unwrap(), b"genuine");
+
+        // no verifier configured: answers are checked against the versionhash
+        let client = |primary: &str, mirrors: Vec<String>, strategy| unchecked(primary, mirrors, strategy).with_versionhash_check(true);
+
+        // the forged copy answers first but is rejected
+        let racing = client(&bad.url(), vec![slow.url(), down.url()], MirrorStrategy::Race);
+        assert_eq!(racing.get_blob_from_cloud(&id).await.unwrap(), b"genuine");
+
+        let sequential = client(&down.url(), vec![bad.url(), slow.url()], MirrorStrategy::Sequential);
+        assert_eq!(sequential.poda_urls().len(), 3);
+        assert_eq!(sequential.get_blob_from_cloud(&id).await.unwrap(), b"genuine");
+
+        let hedged = client(&bad.url(), vec![slow.url()], MirrorStrategy::Hedged { delay: Duration::from_millis(20) });
+        assert_eq!(hedged.get_blob_from_cloud(&id).await.unwrap(), b"genuine");
+
+        // a verifier is checked on top of the versionhash, never instead of it
+        let vetoed = client(&slow.url(), vec![], MirrorStrategy::Sequential)
+            .with_blob_verifier(Arc::new(|_, data: &[u8]| data != b"genuine"));
+        assert!(vetoed.get_blob_from_cloud(&id).await.is_err());
+        let permissive = client(&bad.url(), vec![], MirrorStrategy::Sequential).with_blob_verifier(Arc::new(|_, _| true));
+        assert!(permissive.get_blob_from_cloud(&id).await.is_err());
+
+        let hopeless = client(&down.url(), vec![bad.url()], MirrorStrategy::Race);
+        let err = hopeless.get_blob_from_cloud(&id).await.unwrap_err().to_string();
+        assert!(err.contains("all 2 PoDA mirrors failed"), "{err}");
+        assert!(err.contains("failed verification"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_retrieval_strategies_report_source() {
+        use bitcoin_da_client::BlobSource;
 
+        let id = bitcoin_da_client::blob_versionhash(b"payload");
+        let mut mock_server = std::thread::spawn(|| {
+            Server::new()
+        }).join().expect("Failed to create mock server");
+        let node = mock_server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::Regex(format!(r#""params":\["{}""#, id)))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"result": {"data": hex::encode(b"payload")}, "error": null, "id": 1}).to_string())
+            .expect_at_least(2)
+            .create();
+        mock_server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::Regex(r#""params":\["bb02""#.into()))
+            .with_status(500)
+            .with_body(json!({"result": null, "error": {"code": -32602, "message": "Could not find blob information"}, "id": 1}).to_string())
+            .create();
+        let cloud = mock_server.mock("GET", format!("/blob/{}", id).as_str()).with_status(200).with_body("payload").expect_at_least(2).create();
+        let cloud_bb02 = mock_server.mock("GET", "/blob/bb02").with_status(404).expect(2).create();
+
+        let client = SyscoinClient::new(&mock_server.url(), "user", "password", &mock_server.url(), None, "test_wallet")
+            .unwrap()
+            .with_retrieval_strategy(RetrievalStrategy::CloudOnly);
+        assert_eq!(client.retrieval_strategy(), RetrievalStrategy::CloudOnly);
+
+        let blob = client.get_blob_with_source(&id).await.unwrap();
+        assert_eq!((blob.data.as_slice(), blob.source), (&b"payload"[..], BlobSource::Cloud));
+        let blob = client.get_blob_using(&id, RetrievalStrategy::RpcOnly).await.unwrap();
+        assert_eq!((blob.data.as_slice(), blob.source), (&b"payload"[..], BlobSource::Rpc));
+        let blob = client.get_blob_using(&id, RetrievalStrategy::CloudThenRpc).await.unwrap();
+        assert_eq!(blob.source, BlobSource::Cloud);
+        let blob = client.get_blob_using(&id, RetrievalStrategy::RpcThenCloud).await.unwrap();
+        assert_eq!(blob.source, BlobSource::Rpc);
+
+        let blob = client.get_blob_using(&id, RetrievalStrategy::Parallel).await.unwrap();
+        assert_ne!(blob.source, BlobSource::Cache);
+        assert_eq!(blob.data, b"payload");
+        node.assert();
+        cloud.assert();
+
+        // validators never touch the cloud
+        assert!(client.get_blob_using("bb02", RetrievalStrategy::RpcOnly).await.is_err());
+        let err = client.get_blob_using("bb02", RetrievalStrategy::Parallel).await.unwrap_err().to_string();
+        assert!(err.contains("RPC:") && err.contains("cloud:"), "{err}");
+        assert!(client.get_blob_using("bb02", RetrievalStrategy::CloudThenRpc).await.is_err());
+        cloud_bb02.assert();
+    }
+
+    #[tokio::test]
+    async fn test_blob_metadata_without_download() {
+        let mut mock_server = std::thread::spawn(|| {
+            Server::new()
+        }).join().expect("Failed to create mock server");
+        mock_server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::Regex(r#""params":\["aa01",false\]"#.into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"result": {
+                "versionhash": "aa01", "txid": "cafe", "blockhash": "00ff", "height": 100,
+                "mpt": 1700000000, "datasize": 2048, "chainlock": true,
+            }, "error": null, "id": 1}).to_string())
+            .create();
+        mock_server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::Regex(r#""params":\["(bb02|cc03)",false\]"#.into()))
+            .with_status(500)
+            .with_body(json!({"result": null, "error": {"code": -32602, "message": "Could not find blob information"}, "id": 1}).to_string())
+            .create();
+        let cloud = mock_server.mock("HEAD", "/blob/bb02").with_status(200).with_header("content-length", "42").expect(2).create();
+        mock_server.mock("HEAD", "/blob/cc03").with_status(404).create();
+        let downloads = mock_server.mock("GET", mockito::Matcher::Any).expect(0).create();
+
+        let client = SyscoinClient::new(&mock_server.url(), "user", "password", &mock_server.url(), None, "test_wallet").unwrap();
+
+        let meta = client.get_blob_metadata("0xaa01").await.unwrap().unwrap();
+        assert_eq!((meta.size, meta.height, meta.mpt), (Some(2048), Some(100), Some(1700000000)));
+        assert_eq!((meta.txid.as_deref(), meta.block_hash.as_deref()), (Some("cafe"), Some("00ff")));
+        assert!(meta.chainlocked && meta.on_node);
+
+        let meta = client.get_blob_metadata("bb02").await.unwrap().unwrap();
+        assert_eq!((meta.size, meta.on_node, meta.in_cloud), (Some(42), false, Some(true)));
+        assert!(client.blob_exists("bb02").await.unwrap());
+        assert!(!client.blob_exists("cc03").await.unwrap());
+
+        let validator = client.with_retrieval_strategy(RetrievalStrategy::RpcOnly);
+        assert!(!validator.blob_exists("bb02").await.unwrap());
+        cloud.assert();
+        downloads.assert();
+    }
+
+    #[tokio::test]
+    async fn test_create_blob_skips_final_duplicates() {
+        use bitcoin_da_client::{blob_versionhash, BlobCreation, CreateBlobOptions};
+
+        assert_eq!(blob_versionhash(b""), "69217a3079908094e11121d042354a7c1f55b6482ca1a51e1b250dfd1ed0eef9");
+        assert_eq!(blob_versionhash(b"abc"), "508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982");
+        assert_eq!(blob_versionhash(&[0u8; 64]), "ae09db7cd54f42b490ef09b6bc541af688e4959bb8c53f359a6f56e38ab454a3");
+        let long: Vec<u8> = (0..=255u8).collect();
+        assert_eq!(blob_versionhash(&long), "5fdeb59f681d975f52c8e69c5502e02a12a3afcc5836ba58f42784c439228781");
+
+        let published = b"already on chain";
+        let final_hash = blob_versionhash(published);
+        let mut mock_server = std::thread::spawn(|| {
+            Server::new()
+        }).join().expect("Failed to create mock server");
+        mock_server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::Regex(format!(r#""params":\["{}",false\]"#, final_hash)))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"result": {"versionhash": final_hash, "chainlock": true}, "error": null, "id": 1}).to_string())
+            .expect(2)
+            .create();
+        let unknown = mock_server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::PartialJson(json!({"method": "getnevmblobdata"})))
+            .with_status(500)
+            .with_body(json!({"result": null, "error": {"code": -32602, "message": "Could not find blob information"}, "id": 1}).to_string())
+            .expect(1)
+            .create();
+        let overwrite = mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(mockito::Matcher::Regex(format!(r#""params":\["{}",true,"blake2s"\]"#, hex::encode(published))))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"result": {"versionhash": final_hash}, "error": null, "id": 1}).to_string())
+            .expect(1)
+            .create();
+        let fresh = mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(mockito::Matcher::Regex(r#""params":\["[0-9a-f]+",false,"blake2s"\]"#.into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"result": {"versionhash": "beef"}, "error": null, "id": 1}).to_string())
+            .expect(1)
+            .create();
+
+        let client = SyscoinClient::new(&mock_server.url(), "user", "password", &mock_server.url(), None, "test_wallet").unwrap();
+
+        let creation = client.create_blob_with_options(published, &CreateBlobOptions::default()).await.unwrap();
+        assert_eq!(creation, BlobCreation::AlreadyExists(final_hash.clone()));
+        assert_eq!(client.create_blob(published).await.unwrap(), final_hash);
+
+        let forced = CreateBlobOptions { overwrite_existing: true, ..Default::default() };
+        let creation = client.create_blob_with_options(published, &forced).await.unwrap();
+        assert!(!creation.already_existed());
+        assert_eq!(creation.versionhash(), final_hash);
+
+        let creation = client.create_blob_with_options(b"brand new", &CreateBlobOptions::default()).await.unwrap();
+        assert_eq!(creation, BlobCreation::Created("beef".into()));
+
+        // oversized input is refused before any lookup
+        let too_big = vec![0u8; bitcoin_da_client::MAX_BLOB_SIZE + 1];
+        assert!(client.create_blob_with_options(&too_big, &CreateBlobOptions::default()).await.is_err());
+        unknown.assert();
+        overwrite.assert();
+        fresh.assert();
+    }
+
+    #[tokio::test]
+    async fn test_create_blobs_keeps_input_order() {
+        use bitcoin_da_client::{BatchOptions, MAX_BLOB_SIZE};
+
+        let mut mock_server = std::thread::spawn(|| {
+            Server::new()
+        }).join().expect("Failed to create mock server");
+        let store = mock_blob_store(&mut mock_server);
+        // 0.002 SYS/kvb -> 2 sat per blob byte
+        mock_blob_base_fee(&mut mock_server, 0.002);
+        let client = SyscoinClient::new(&mock_server.url(), "user", "password", &mock_server.url(), None, "test_wallet").unwrap();
+
+        let too_big = vec![0u8; MAX_BLOB_SIZE + 1];
+        let err = client.create_blobs(&[b"fine", &too_big], &BatchOptions::default()).await.unwrap_err();
+        assert!(err.to_string().contains("blob 1 size"), "{err}");
+        assert!(store.lock().unwrap().is_empty());
+
+        let items: Vec<Vec<u8>> = (0..6u8).map(|i| vec![i; 10 + i as usize]).collect();
+        let refs: Vec<&[u8]> = items.iter().map(Vec::as_slice).collect();
+        for options in [
+            BatchOptions { max_concurrency: 3, ..Default::default() },
+            BatchOptions { serialize_funding: true, ..Default::default() },
+        ] {
+            let batch = client.create_blobs(&refs, &options).await.unwrap();
+            assert_eq!(batch.results.len(), items.len());
+            let stored = store.lock().unwrap();
+            for (result, item) in batch.results.iter().zip(&items) {
+                assert_eq!(stored[result.as_ref().unwrap()], hex::encode(item));
+            }
+            assert_eq!((batch.summary.created, batch.summary.already_existed, batch.summary.failed), (6, 0, 0));
+            assert_eq!(batch.summary.total_bytes, 75);
+            assert_eq!(batch.summary.estimated_fee, Some(150));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_blob_retention_and_archiver() {
+        use bitcoin_da_client::{blob_versionhash, ArchiverConfig, BlobArchiver, BlobStore, FsBlobStore};
+        use std::sync::Arc;
+
+        let mut mock_server = std::thread::spawn(|| {
+            Server::new()
+        }).join().expect("Failed to create mock server");
+        let blob = |server: &mut mockito::ServerGuard, id: &str, mpt: u64, data: &[u8]| {
+            server
+                .mock("POST", "/")
+                .match_body(mockito::Matcher::Regex(format!(r#""params":\["{}",false\]"#, id)))
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(json!({"result": {"versionhash": id, "txid": "cafe", "mpt": mpt}, "error": null, "id": 1}).to_string())
+                .create();
+            server
+                .mock("POST", "/")
+                .match_body(mockito::Matcher::Regex(format!(r#""params":\["{}",true\]"#, id)))
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(json!({"result": {"data": hex::encode(data)}, "error": null, "id": 1}).to_string())
+                .create();
+        };
+        // six hour window: `soon` has ten minutes left, `later` almost all of it
+        let soon = blob_versionhash(b"expiring soon");
+        let later = blob_versionhash(b"plenty of time");
+        blob(&mut mock_server, &soon, 1_000, b"expiring soon");
+        blob(&mut mock_server, &later, 21_940, b"plenty of time");
+        mock_server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::Regex(r#""params":\["([0-9a-f]{64}|aa01)""#.into()))
+            .with_status(500)
+            .with_body(json!({"result": null, "error": {"code": -32602, "message": "Could not find blob information"}, "id": 1}).to_string())
+            .create();
+        mock_server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::PartialJson(json!({"method": "getblockchaininfo"})))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"result": {"blocks": 500, "mediantime": 22_000}, "error": null, "id": 1}).to_string())
+            .create();
+        let pruned = blob_versionhash(b"already pruned");
+        mock_server.mock("GET", format!("/blob/{}", pruned).as_str()).with_status(200).with_body("already pruned").create();
+        // a cloud copy that does not belong to aa01
+        mock_server.mock("GET", "/blob/aa01").with_status(200).with_body("not aa01").create();
+
+        let client = SyscoinClient::new(&mock_server.url(), "user", "password", &mock_server.url(), None, "test_wallet").unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(FsBlobStore::open(dir.path()).await.unwrap());
+
+        // without a check configured, cloud copies are never archived
+        let mut unchecked = BlobArchiver::new(&client, store.clone(), ArchiverConfig::default());
+        let report = unchecked.track(&pruned).poll().await;
+        assert_eq!((report.archived.len(), report.failed.len()), (0, 1));
+        assert!(report.failed[0].1.to_string().contains("unverified"), "{}", report.failed[0].1);
+        let client = client.with_versionhash_check(true);
+
+        let retention = client.get_blob_retention(&soon).await.unwrap();
+        assert!(retention.on_node);
+        assert_eq!((retention.published_at, retention.prune_time), (Some(1_000), Some(22_600)));
+        assert_eq!((retention.prune_height, retention.remaining), (Some(504), Some(Duration::from_secs(600))));
+        assert!(!client.get_blob_retention(&pruned).await.unwrap().on_node);
+
+        let mut archiver = BlobArchiver::new(&client, store.clone(), ArchiverConfig::default());
+        archiver.track(&format!("0x{}", soon.to_uppercase())).track(&later).track(&pruned).track("aa01");
+
+        let report = archiver.poll().await;
+        let mut archived = vec![soon.clone(), pruned.clone()];
+        archived.sort();
+        assert_eq!(report.archived, archived);
+        assert_eq!(report.pending, 1);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].0, "aa01");
+        assert!(report.failed[0].1.to_string().contains("failed verification"), "{}", report.failed[0].1);
+        assert_eq!(store.get(&soon).await.unwrap().unwrap(), b"expiring soon");
+        assert_eq!(store.get(&pruned).await.unwrap().unwrap(), b"already pruned");
+        assert!(!store.contains(&later).await.unwrap());
+        assert!(!store.contains("aa01").await.unwrap());
+        let mut tracked = vec!["aa01".to_string(), later.clone()];
+        tracked.sort();
+        assert_eq!(archiver.tracked().collect::<Vec<_>>(), tracked);
+
+        // a shorter window makes `later` due too
+        let short = SyscoinClient::new(&mock_server.url(), "user", "password", &mock_server.url(), None, "test_wallet")
+            .unwrap()
+            .with_blob_retention(Duration::from_secs(30 * 60));
+        let mut archiver = BlobArchiver::new(&short, store.clone(), ArchiverConfig::default());
+        archiver.track(&later);
+        archiver.run().await;
+        assert_eq!(store.get(&later).await.unwrap().unwrap(), b"plenty of time");
+        assert!(store.put("../escape", b"x").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_blob_store_write_through() {
+        use bitcoin_da_client::{BlobStore, FsBlobStore, MemoryBlobStore};
+        use std::sync::Arc;
+
+        let mut mock_server = std::thread::spawn(|| {
+            Server::new()
+        }).join().expect("Failed to create mock server");
+        mock_blob_echo(&mut mock_server);
+        let cloud_id = bitcoin_da_client::blob_versionhash(b"cloud copy");
+        mock_server.mock("GET", format!("/blob/{}", cloud_id).as_str()).with_status(200).with_body("cloud copy").create();
+        mock_server.mock("GET", "/blob/dd04").with_status(200).with_body("cloud copy").create();
+
+        let memory = Arc::new(MemoryBlobStore::new());
+        let client = SyscoinClient::new(&mock_server.url(), "user", "password", &mock_server.url(), None, "test_wallet")
+            .unwrap()
+            .with_blob_store(memory.clone());
+
+        assert_eq!(client.create_blob(b"keep a copy").await.unwrap(), "cafe");
+        assert_eq!(memory.get("0xCAFE").await.unwrap().unwrap(), b"keep a copy");
+        // cloud copies are stored only once a check accepts them
+        client.get_blob_using(&cloud_id, RetrievalStrategy::CloudOnly).await.unwrap();
+        assert_eq!(memory.list().await.unwrap(), vec!["cafe"]);
+        let client = client.with_versionhash_check(true);
+        assert!(client.get_blob_using("dd04", RetrievalStrategy::CloudOnly).await.is_err());
+        assert_eq!(memory.list().await.unwrap(), vec!["cafe"]);
+        client.get_blob_using(&cloud_id, RetrievalStrategy::CloudOnly).await.unwrap();
+        let mut both = vec!["cafe".to_string(), cloud_id.clone()];
+        both.sort();
+        assert_eq!(memory.list().await.unwrap(), both);
+
+        let dir = tempfile::tempdir().unwrap();
+        let disk = Arc::new(FsBlobStore::open(dir.path()).await.unwrap());
+        let verified = client
+            .with_blob_store(disk.clone())
+            .with_blob_verifier(Arc::new(|_, data: &[u8]| data == b"cloud copy"));
+        verified.get_blob("cafe").await.unwrap();
+        verified.get_blob_using(&cloud_id, RetrievalStrategy::CloudOnly).await.unwrap();
+        assert_eq!(disk.list().await.unwrap(), both);
+        assert!(disk.delete(&cloud_id).await.unwrap());
+        assert!(!disk.delete(&cloud_id).await.unwrap());
+        assert_eq!(disk.list().await.unwrap(), vec!["cafe"]);
+        assert!(memory.delete("cafe").await.unwrap());
+        assert!(memory.delete(&cloud_id).await.unwrap());
+        assert!(memory.list().await.unwrap().is_empty());
+
+        // streamed uploads are written through too
+        let streamed = SyscoinClient::new(&mock_server.url(), "user", "password", &mock_server.url(), None, "test_wallet")
+            .unwrap()
+            .with_blob_store(memory.clone());
+        let id = streamed
+            .create_blob_from_reader(std::io::Cursor::new(b"streamed copy".to_vec()), 13)
+            .await
+            .unwrap();
+        assert_eq!(memory.get(&id).await.unwrap().unwrap(), b"streamed copy");
+    }
+
+    #[cfg(feature = "s3")]
+    #[tokio::test]
+    async fn test_s3_blob_store_signs_requests() {
+        use bitcoin_da_client::{BlobStore, S3BlobStore, S3Config};
+        use mockito::Matcher;
+
+        let mut s3 = std::thread::spawn(|| {
+            Server::new()
+        }).join().expect("Failed to create mock server");
+        let signed = Matcher::Regex(
+            r"^AWS4-HMAC-SHA256 Credential=minio/\d{8}/eu-central-1/s3/aws4_request, SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature=[0-9a-f]{64}$".into(),
+        );
+        let put = s3
+            .mock("PUT", "/archive/blobs/beef01")
+            .match_header("authorization", signed.clone())
+            .match_header("x-amz-content-sha256", "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824")
+            .match_body("hello")
+            .with_status(200)
+            .create();
+        s3.mock("GET", "/archive/blobs/beef01").match_header("authorization", signed.clone()).with_status(200).with_body("hello").create();
+        s3.mock("GET", "/archive/blobs/beef02").with_status(404).with_body("<Error><Code>NoSuchKey</Code></Error>").create();
+        s3.mock("HEAD", "/archive/blobs/beef01").with_status(200).create();
+        s3.mock("HEAD", "/archive/blobs/beef02").with_status(404).create();
+        let delete = s3.mock("DELETE", "/archive/blobs/beef01").with_status(204).expect(1).create();
+        s3.mock("GET", "/archive")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("list-type".into(), "2".into()),
+                Matcher::UrlEncoded("prefix".into(), "blobs/".into()),
+                Matcher::UrlEncoded("continuation-token".into(), "next/page".into()),
+            ]))
+            .with_status(200)
+            .with_body("<ListBucketResult><IsTruncated>false</IsTruncated><Contents><Key>blobs/aa03</Key></Contents></ListBucketResult>")
+            .create();
+        s3.mock("GET", "/archive")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("list-type".into(), "2".into()),
+                Matcher::UrlEncoded("prefix".into(), "blobs/".into()),
+            ]))
+            .with_status(200)
+            .with_body(
+                "<ListBucketResult><IsTruncated>true</IsTruncated><NextContinuationToken>next/page</NextContinuationToken>\
+                 <Contents><Key>blobs/beef01</Key></Contents><Contents><Key>blobs/notes.txt</Key></Contents></ListBucketResult>",
+            )
+            .create();
+        s3.mock("PUT", "/archive/blobs/dead00").with_status(403).with_body("<Error><Code>AccessDenied</Code></Error>").create();
+
+        let config = S3Config::new(&s3.url(), "archive", "minio", SecretString::from("minio-secret"))
+            .with_region("eu-central-1")
+            .with_prefix("blobs/");
+        let store = S3BlobStore::new(config).unwrap();
+
+        store.put("0xBEEF01", b"hello").await.unwrap();
+        put.assert();
+        assert_eq!(store.get("beef01").await.unwrap().unwrap(), b"hello");
+        assert_eq!(store.get("beef02").await.unwrap(), None);
+        assert_eq!(store.list().await.unwrap(), vec!["aa03", "beef01"]);
+        assert!(store.delete("beef01").await.unwrap());
+        assert!(!store.delete("beef02").await.unwrap());
+        delete.assert();
+        let err = store.put("dead00", b"x").await.unwrap_err().to_string();
+        assert!(err.contains("403") && err.contains("AccessDenied"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_chain_indexer_follows_reorgs() {
+        use bitcoin_da_client::{BlobIndex, ChainIndexer, IndexScope, IndexerConfig};
+        use std::sync::{Arc, Mutex};
+
+        // Hand-built in the shape of verbose `getblock` output (not captured from a node):
+        // the carrier's OP_RETURN pushes the length-prefixed versionhash
+        fn carrier(txid: &str, version: u32, versionhash: &str) -> serde_json::Value {
+            json!({
+                "txid": txid,
+                "version": version,
+                "vout": [
+                    { "value": 0.0, "n": 0, "scriptPubKey": {
+                        "asm": format!("OP_RETURN 20{}", versionhash),
+                        "hex": format!("6a2120{}", versionhash),
+                        "type": "nulldata"
+                    } },
+                    { "value": 0.5, "n": 1, "scriptPubKey": { "asm": "0 abcd", "type": "witness_v0_keyhash" } }
+                ]
+            })
+        }
+        let (hash_a, hash_b, hash_c) = ("aa".repeat(32), "bb".repeat(32), "cc".repeat(32));
+        // tx_c is unknown to the node, so its versionhash comes from the script
+        let known = std::collections::HashMap::from([("tx_a", hash_a.clone()), ("tx_b", hash_b.clone())]);
+        let block = |hash: &str, prev: Option<&str>, txs: Vec<serde_json::Value>| (hash.to_owned(), json!({"hash": hash, "previousblockhash": prev, "tx": txs}));
+        let chain = Arc::new(Mutex::new(vec![
+            block("h0", None, vec![]),
+            block("h1", Some("h0"), vec![
+                carrier("not_a_blob", 2, &hash_c),
+                json!({"txid": "no_commitment", "version": 137, "vout": []}),
+            ]),
+            block("h2", Some("h1"), vec![carrier("tx_a", 137, &hash_a)]),
+            block("h3", Some("h2"), vec![carrier("tx_b", 137, &hash_b)]),
+        ]));
+
+        let mut mock_server = std::thread::spawn(|| {
+            Server::new()
+        }).join().expect("Failed to create mock server");
+        let served = chain.clone();
+        mock_server
+            .mock("POST", "/")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body_from_request(move |req| {
+                let body: serde_json::Value = serde_json::from_slice(req.body().unwrap()).unwrap();
+                let chain = served.lock().unwrap();
+                let result = match body["method"].as_str().unwrap() {
+                    "getblockcount" => json!(chain.len() - 1),
+                    "getblockhash" => json!(chain[body["params"][0].as_u64().unwrap() as usize].0),
+                    "getblock" => chain.iter().find(|(h, _)| h == body["params"][0].as_str().unwrap()).unwrap().1.clone(),
+                    "getnevmblobdata" => match known.get(body["params"][0].as_str().unwrap()) {
+                        Some(hash) => json!({"versionhash": format!("0x{}", hash.to_uppercase()), "datasize": 100}),
+                        None => {
+                            let error = json!({"code": -32602, "message": "Could not find blob information for versionhash"});
+                            return json!({"result": null, "error": error, "id": 1}).to_string().into();
+                        }
+                    },
+                    other => panic!("unexpected {other}"),
+                };
+                json!({"result": result, "error": null, "id": 1}).to_string().into()
+            })
+            .create();
+        mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .match_body(mockito::Matcher::Regex(r#""params":\["tx_a"\]"#.into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"result": {"txid": "tx_a"}, "error": null, "id": 1}).to_string())
+            .create();
+        mock_server
+            .mock("POST", "/wallet/test_wallet")
+            .with_status(500)
+            .with_body(json!({"result": null, "error": {"code": -5, "message": "Invalid or non-wallet transaction id"}, "id": 1}).to_string())
+            .create();
+
+        let client = SyscoinClient::new(&mock_server.url(), "user", "password", &mock_server.url(), None, "test_wallet").unwrap();
+        let mut indexer = ChainIndexer::new(&client, IndexerConfig { blocks_per_poll: 3, ..Default::default() });
+
+        let update = indexer.poll().await.unwrap();
+        assert_eq!((update.indexed_height, update.caught_up), (Some(2), false));
+        assert_eq!(update.added.len(), 1);
+        let update = indexer.poll().await.unwrap();
+        assert_eq!((update.indexed_height, update.caught_up), (Some(3), true));
+        let blob_b = indexer.index().get(&hash_b).unwrap().clone();
+        assert_eq!((blob_b.txid.as_str(), blob_b.height, blob_b.size), ("tx_b", 3, Some(100)));
+        assert_eq!(indexer.index().len(), 2);
+
+        // block 3 is replaced; tx_b drops out and tx_c lands on the new branch
+        {
+            let mut chain = chain.lock().unwrap();
+            chain.truncate(3);
+            chain.push(block("h3x", Some("h2"), vec![]));
+            chain.push(block("h4x", Some("h3x"), vec![carrier("tx_c", 137, &hash_c)]));
+        }
+        let update = indexer.poll().await.unwrap();
+        assert_eq!(update.rolled_back, vec![blob_b]);
+        assert_eq!(update.added.iter().map(|b| b.txid.as_str()).collect::<Vec<_>>(), vec!["tx_c"]);
+        assert_eq!(indexer.index().tip(), Some((4, "h4x")));
+        assert!(indexer.index().get(&hash_b).is_none());
+        let blob_c = indexer.index().by_txid("tx_c").unwrap();
+        assert_eq!((blob_c.versionhash.as_str(), blob_c.height, blob_c.size), (hash_c.as_str(), 4, None));
+        assert_eq!(indexer.index().in_heights(..=3).count(), 1);
+
+        let saved = serde_json::to_string(&indexer.into_index()).unwrap();
+        let resumed: BlobIndex = serde_json::from_str(&saved).unwrap();
+        let mut indexer = ChainIndexer::new(&client, IndexerConfig::default()).with_index(resumed);
+        assert!(indexer.poll().await.unwrap().added.is_empty());
+        assert_eq!(indexer.index().get(&hash_a.to_uppercase()).unwrap().txid, "tx_a");
+
+        let mut ours = ChainIndexer::new(&client, IndexerConfig { scope: IndexScope::Wallet, ..Default::default() });
+        ours.poll().await.unwrap();
+        let mine: Vec<_> = ours.index().iter().map(|b| (b.txid.as_str(), b.size)).collect();
+        assert_eq!(mine, vec![("tx_a", Some(100))]);
+
+        // an index whose genesis is from another chain is dropped and rebuilt, not retried forever
+        let foreign: BlobIndex = serde_json::from_value(json!({"blocks": {"0": "other_genesis"}, "blobs": {}})).unwrap();
+        let mut indexer = ChainIndexer::new(&client, IndexerConfig { blocks_per_poll: 2, ..Default::default() }).with_index(foreign);
+        let update = indexer.poll().await.unwrap();
+        assert_eq!((update.indexed_height, indexer.index().tip()), (Some(1), Some((1, "h1"))));
+    }
+
+}